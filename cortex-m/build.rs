@@ -1,4 +1,6 @@
 use std::env;
+use std::fs;
+use std::path::PathBuf;
 
 fn main() {
     let target = env::var("TARGET").unwrap();
@@ -31,4 +33,42 @@ fn main() {
     if target.ends_with("-eabihf") {
         println!("cargo:rustc-cfg=has_fpu");
     }
+
+    if env::var_os("CARGO_FEATURE_CRITICAL_SECTION_BASEPRI").is_some() {
+        basepri_ceiling();
+    }
+}
+
+/// Writes the BASEPRI value used by the `critical-section-basepri` implementation, read from the
+/// `CORTEX_M_BASEPRI_CEILING` environment variable.
+fn basepri_ceiling() {
+    const VAR: &str = "CORTEX_M_BASEPRI_CEILING";
+    println!("cargo:rerun-if-env-changed={}", VAR);
+
+    let value = env::var(VAR).unwrap_or_else(|_| {
+        panic!(
+            "the `critical-section-basepri` feature requires the {} environment variable, \
+             set it to the BASEPRI value of the critical sections",
+            VAR
+        )
+    });
+    let value = value.trim();
+    let ceiling = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => value.parse::<u8>(),
+    };
+    let ceiling = match ceiling {
+        Ok(ceiling) if ceiling != 0 => ceiling,
+        // BASEPRI = 0 does not mask any interrupt
+        _ => panic!(
+            "{} must be a number between 1 and 255, got `{}`",
+            VAR, value
+        ),
+    };
+
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("basepri_ceiling.rs");
+    fs::write(out, format!("const CEILING: u8 = {:#04x};\n", ceiling)).unwrap();
 }
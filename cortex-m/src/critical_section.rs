@@ -0,0 +1,82 @@
+#[cfg(all(
+    feature = "critical-section-single-core",
+    feature = "critical-section-basepri"
+))]
+compile_error!(
+    "the `critical-section-single-core` and `critical-section-basepri` features can not be \
+     enabled together"
+);
+
+#[cfg(all(feature = "critical-section-basepri", any(armv6m, armv8m_base)))]
+compile_error!(
+    "the `critical-section-basepri` feature is not supported on ARMv6-M and ARMv8-M Baseline, \
+     which do not implement BASEPRI; use `critical-section-single-core` instead"
+);
+
+#[cfg(feature = "critical-section-single-core")]
+mod single_core {
+    use critical_section::{set_impl, Impl, RawRestoreState};
+
+    use crate::interrupt;
+    use crate::register::primask;
+
+    struct SingleCoreCriticalSection;
+    set_impl!(SingleCoreCriticalSection);
+
+    unsafe impl Impl for SingleCoreCriticalSection {
+        unsafe fn acquire() -> RawRestoreState {
+            let was_active = primask::read().is_active();
+            interrupt::disable();
+            was_active
+        }
+
+        unsafe fn release(was_active: RawRestoreState) {
+            // Only re-enable interrupts if they were enabled before the critical section.
+            if was_active {
+                interrupt::enable()
+            }
+        }
+    }
+}
+
+#[cfg(all(
+    feature = "critical-section-basepri",
+    not(feature = "critical-section-single-core"),
+    not(any(armv6m, armv8m_base))
+))]
+mod basepri {
+    use core::sync::atomic::{compiler_fence, Ordering};
+
+    use critical_section::{set_impl, Impl, RawRestoreState};
+
+    use crate::register::{basepri, basepri_max};
+
+    include!(concat!(env!("OUT_DIR"), "/basepri_ceiling.rs"));
+
+    struct BasepriCriticalSection;
+    set_impl!(BasepriCriticalSection);
+
+    unsafe impl Impl for BasepriCriticalSection {
+        unsafe fn acquire() -> RawRestoreState {
+            let previous = basepri::read();
+            // BASEPRI_MAX only raises the priority mask, nested critical sections and code that
+            // already runs with a higher mask are left unchanged.
+            basepri_max::write(CEILING);
+            // Unimplemented priority bits read as zero, a ceiling with none of the implemented
+            // bits set would leave BASEPRI at zero and not mask any interrupt.
+            assert!(
+                basepri::read() != 0,
+                "CORTEX_M_BASEPRI_CEILING has none of the implemented priority bits set"
+            );
+            // Ensure no subsequent memory accesses are reordered to before BASEPRI is raised.
+            compiler_fence(Ordering::SeqCst);
+            previous
+        }
+
+        unsafe fn release(previous: RawRestoreState) {
+            // Ensure no preceeding memory accesses are reordered to after BASEPRI is restored.
+            compiler_fence(Ordering::SeqCst);
+            basepri::write(previous);
+        }
+    }
+}
@@ -0,0 +1,547 @@
+//! Exception stack frames and `EXC_RETURN` values
+//!
+//! On exception entry, the processor pushes the caller-saved registers on the active stack and
+//! loads an `EXC_RETURN` value in the Link Register. The types of this module describe those
+//! frames and values so that handlers can inspect and modify them.
+//!
+//! # References
+//!
+//! - ARMv7-M Architecture Reference Manual - Section B1.5.6 Exception entry behavior
+//! - Armv8-M Architecture Reference Manual - Section B3.19 Exception entry, context stacking
+
+use core::mem::size_of;
+
+use crate::register::control::Spsel;
+
+/// Registers stacked by the processor on exception entry
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub struct ExceptionFrame {
+    r0: u32,
+    r1: u32,
+    r2: u32,
+    r3: u32,
+    r12: u32,
+    lr: u32,
+    pc: u32,
+    xpsr: u32,
+}
+
+const _: () = assert!(size_of::<ExceptionFrame>() == 8 * 4);
+
+impl ExceptionFrame {
+    /// Returns a reference to the frame stacked at `sp`.
+    ///
+    /// # Safety
+    ///
+    /// `sp` must be the stack pointer of an exception frame, for example the MSP or PSP value
+    /// read on handler entry, and the frame must not be accessed through another reference while
+    /// the returned one is alive.
+    #[inline]
+    pub unsafe fn from_sp<'a>(sp: *const u32) -> &'a Self {
+        &*(sp as *const Self)
+    }
+
+    /// Returns a mutable reference to the frame stacked at `sp`.
+    ///
+    /// # Safety
+    ///
+    /// See [`ExceptionFrame::from_sp`], in addition the preempted code must expect `r0` to `r3`
+    /// to be modified, which is the case of code executing an `SVC` instruction with those
+    /// registers as outputs.
+    #[inline]
+    pub unsafe fn from_sp_mut<'a>(sp: *mut u32) -> &'a mut Self {
+        &mut *(sp as *mut Self)
+    }
+
+    /// Returns the value of (general purpose) register 0.
+    #[inline]
+    pub fn r0(&self) -> u32 {
+        self.r0
+    }
+
+    /// Returns the value of (general purpose) register 1.
+    #[inline]
+    pub fn r1(&self) -> u32 {
+        self.r1
+    }
+
+    /// Returns the value of (general purpose) register 2.
+    #[inline]
+    pub fn r2(&self) -> u32 {
+        self.r2
+    }
+
+    /// Returns the value of (general purpose) register 3.
+    #[inline]
+    pub fn r3(&self) -> u32 {
+        self.r3
+    }
+
+    /// Returns the value of (general purpose) register 12.
+    #[inline]
+    pub fn r12(&self) -> u32 {
+        self.r12
+    }
+
+    /// Returns the value of the Link Register.
+    #[inline]
+    pub fn lr(&self) -> u32 {
+        self.lr
+    }
+
+    /// Returns the value of the Program Counter.
+    #[inline]
+    pub fn pc(&self) -> u32 {
+        self.pc
+    }
+
+    /// Returns the value of the Program Status Register.
+    #[inline]
+    pub fn xpsr(&self) -> u32 {
+        self.xpsr
+    }
+
+    /// Returns `true` if a padding word was inserted above the frame to align it to 8 bytes, as
+    /// recorded in bit 9 of the stacked xPSR.
+    #[inline]
+    pub fn is_padded(&self) -> bool {
+        self.xpsr & (1 << 9) != 0
+    }
+
+    /// Sets the stacked value of (general purpose) register 0, for example to return a value
+    /// from an SVC handler.
+    #[inline]
+    pub fn set_r0(&mut self, value: u32) {
+        self.r0 = value;
+    }
+
+    /// Sets the stacked value of (general purpose) register 1.
+    #[inline]
+    pub fn set_r1(&mut self, value: u32) {
+        self.r1 = value;
+    }
+
+    /// Sets the stacked value of (general purpose) register 2.
+    #[inline]
+    pub fn set_r2(&mut self, value: u32) {
+        self.r2 = value;
+    }
+
+    /// Sets the stacked value of (general purpose) register 3.
+    #[inline]
+    pub fn set_r3(&mut self, value: u32) {
+        self.r3 = value;
+    }
+
+    /// Sets the stacked value of (general purpose) register 12.
+    ///
+    /// # Safety
+    ///
+    /// This affects the `r12` register of the preempted code, which must not rely on it getting
+    /// restored to its previous value.
+    #[inline]
+    pub unsafe fn set_r12(&mut self, value: u32) {
+        self.r12 = value;
+    }
+
+    /// Sets the stacked value of the Link Register.
+    ///
+    /// # Safety
+    ///
+    /// This affects the `lr` register of the preempted code, which must not rely on it getting
+    /// restored to its previous value.
+    #[inline]
+    pub unsafe fn set_lr(&mut self, value: u32) {
+        self.lr = value;
+    }
+
+    /// Sets the stacked value of the Program Counter.
+    ///
+    /// # Safety
+    ///
+    /// This affects the `pc` register of the preempted code, which must not rely on it getting
+    /// restored to its previous value.
+    #[inline]
+    pub unsafe fn set_pc(&mut self, value: u32) {
+        self.pc = value;
+    }
+
+    /// Sets the stacked value of the Program Status Register.
+    ///
+    /// # Safety
+    ///
+    /// This affects the `xPSR` registers (`IPSR`, `APSR`, and `EPSR`) of the preempted code, which
+    /// must not rely on them getting restored to their previous value.
+    #[inline]
+    pub unsafe fn set_xpsr(&mut self, value: u32) {
+        self.xpsr = value;
+    }
+}
+
+/// Registers stacked by the processor on exception entry when the floating-point context is
+/// active
+///
+/// The floating-point registers are only written to the stack when lazy state preservation is
+/// disabled or once the handler executed a floating-point instruction; before that, the space is
+/// reserved but holds stale data.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub struct ExtendedExceptionFrame {
+    basic: ExceptionFrame,
+    s: [u32; 16],
+    fpscr: u32,
+    _reserved: u32,
+}
+
+const _: () = assert!(size_of::<ExtendedExceptionFrame>() == 26 * 4);
+
+impl ExtendedExceptionFrame {
+    /// Returns a reference to the frame stacked at `sp`.
+    ///
+    /// # Safety
+    ///
+    /// See [`ExceptionFrame::from_sp`], in addition the frame must be an extended frame, which
+    /// is indicated by the `EXC_RETURN` value of the exception.
+    #[inline]
+    pub unsafe fn from_sp<'a>(sp: *const u32) -> &'a Self {
+        &*(sp as *const Self)
+    }
+
+    /// Returns a mutable reference to the frame stacked at `sp`.
+    ///
+    /// # Safety
+    ///
+    /// See [`ExtendedExceptionFrame::from_sp`], in addition the preempted code must expect `r0`
+    /// to `r3` and `s0` to `s15` to be modified.
+    #[inline]
+    pub unsafe fn from_sp_mut<'a>(sp: *mut u32) -> &'a mut Self {
+        &mut *(sp as *mut Self)
+    }
+
+    /// Returns the integer part of the frame.
+    #[inline]
+    pub fn basic(&self) -> &ExceptionFrame {
+        &self.basic
+    }
+
+    /// Returns the integer part of the frame.
+    #[inline]
+    pub fn basic_mut(&mut self) -> &mut ExceptionFrame {
+        &mut self.basic
+    }
+
+    /// Returns the value of the single-precision registers `s0` to `s15`.
+    #[inline]
+    pub fn s(&self) -> &[u32; 16] {
+        &self.s
+    }
+
+    /// Returns the value of the Floating-Point Status and Control Register.
+    #[inline]
+    pub fn fpscr(&self) -> u32 {
+        self.fpscr
+    }
+
+    /// Sets the stacked value of the single-precision register `s<n>`, for example to return a
+    /// floating-point value with the hard-float ABI. Indexes greater than 15 are ignored.
+    #[inline]
+    pub fn set_s(&mut self, n: usize, value: u32) {
+        if let Some(s) = self.s.get_mut(n) {
+            *s = value;
+        }
+    }
+
+    /// Sets the stacked value of the Floating-Point Status and Control Register.
+    ///
+    /// # Safety
+    ///
+    /// This affects the `FPSCR` register of the preempted code, which must not rely on it getting
+    /// restored to its previous value.
+    #[inline]
+    pub unsafe fn set_fpscr(&mut self, value: u32) {
+        self.fpscr = value;
+    }
+}
+
+/// Additional state context stacked below the exception frame on ARMv8-M, when an exception
+/// taken from Secure state is handled in Non-secure state
+#[cfg(any(armv8m, native))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[repr(C)]
+pub struct AdditionalStateContext {
+    integrity_signature: u32,
+    _reserved: u32,
+    r4_r11: [u32; 8],
+}
+
+#[cfg(any(armv8m, native))]
+const _: () = assert!(size_of::<AdditionalStateContext>() == 10 * 4);
+
+#[cfg(any(armv8m, native))]
+impl AdditionalStateContext {
+    /// Integrity signature of a context stacked without floating-point state
+    pub const INTEGRITY_SIGNATURE: u32 = 0xFEFA_125B;
+
+    /// Integrity signature of a context stacked with floating-point state
+    pub const INTEGRITY_SIGNATURE_FP: u32 = 0xFEFA_125A;
+
+    /// Returns a reference to the context stacked at `sp`.
+    ///
+    /// # Safety
+    ///
+    /// See [`ExceptionFrame::from_sp`], in addition the context must have been stacked, which
+    /// is indicated by the `EXC_RETURN` value of the exception.
+    #[inline]
+    pub unsafe fn from_sp<'a>(sp: *const u32) -> &'a Self {
+        &*(sp as *const Self)
+    }
+
+    /// Returns a mutable reference to the context stacked at `sp`.
+    ///
+    /// # Safety
+    ///
+    /// See [`AdditionalStateContext::from_sp`].
+    #[inline]
+    pub unsafe fn from_sp_mut<'a>(sp: *mut u32) -> &'a mut Self {
+        &mut *(sp as *mut Self)
+    }
+
+    /// Returns the integrity signature.
+    #[inline]
+    pub fn integrity_signature(&self) -> u32 {
+        self.integrity_signature
+    }
+
+    /// Returns `true` if the integrity signature is one of the two valid values.
+    #[inline]
+    pub fn is_signature_valid(&self) -> bool {
+        self.integrity_signature == Self::INTEGRITY_SIGNATURE
+            || self.integrity_signature == Self::INTEGRITY_SIGNATURE_FP
+    }
+
+    /// Returns the value of the callee-saved registers `r4` to `r11`.
+    #[inline]
+    pub fn r4_r11(&self) -> &[u32; 8] {
+        &self.r4_r11
+    }
+}
+
+const EXC_RETURN_ES: u32 = 1 << 0;
+const EXC_RETURN_SPSEL: u32 = 1 << 2;
+const EXC_RETURN_MODE: u32 = 1 << 3;
+const EXC_RETURN_FTYPE: u32 = 1 << 4;
+#[cfg(any(armv8m, native))]
+const EXC_RETURN_DCRS: u32 = 1 << 5;
+#[cfg(any(armv8m, native))]
+const EXC_RETURN_S: u32 = 1 << 6;
+
+// Bits that can change between two valid EXC_RETURN values, all the others are fixed.
+#[cfg(not(any(armv8m, native)))]
+const EXC_RETURN_FIELDS: u32 = EXC_RETURN_SPSEL | EXC_RETURN_MODE | EXC_RETURN_FTYPE;
+#[cfg(any(armv8m, native))]
+const EXC_RETURN_FIELDS: u32 = EXC_RETURN_ES
+    | EXC_RETURN_SPSEL
+    | EXC_RETURN_MODE
+    | EXC_RETURN_FTYPE
+    | EXC_RETURN_DCRS
+    | EXC_RETURN_S;
+
+// Value of the fixed bits: the `0xFF` prefix, ones down to bit 5 (bit 7 on ARMv8-M) and a zero
+// in bit 1. ES is always set on ARMv6-M and ARMv7-M.
+#[cfg(not(any(armv8m, native)))]
+const EXC_RETURN_FIXED: u32 = 0xFFFF_FFE0 | EXC_RETURN_ES;
+#[cfg(any(armv8m, native))]
+const EXC_RETURN_FIXED: u32 = 0xFFFF_FF80;
+
+/// Processor mode to return to
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Mode {
+    /// Handler mode, the exception preempted another exception
+    Handler,
+    /// Thread mode
+    Thread,
+}
+
+/// Type of the stacked exception frame
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FrameType {
+    /// [`ExceptionFrame`], without floating-point state
+    Basic,
+    /// [`ExtendedExceptionFrame`], with floating-point state
+    Extended,
+}
+
+/// Security state
+#[cfg(any(armv8m, native))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum SecurityState {
+    /// Secure state
+    Secure,
+    /// Non-secure state
+    NonSecure,
+}
+
+/// `EXC_RETURN` value, loaded in the Link Register on exception entry
+///
+/// Branching to this value returns from the exception; it describes the mode and the stack to
+/// return to and the layout of the stacked frame.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ExcReturn {
+    bits: u32,
+}
+
+impl ExcReturn {
+    /// Creates an `EXC_RETURN` value.
+    ///
+    /// On ARMv8-M the value returns to Non-secure state, from an exception taken to Non-secure
+    /// state, with the default callee register stacking rules.
+    ///
+    /// Returns `None` for the reserved combination of Handler mode and the process stack, and for
+    /// an extended frame on ARMv6-M.
+    #[inline]
+    pub fn new(mode: Mode, stack: Spsel, frame_type: FrameType) -> Option<Self> {
+        #[cfg(not(any(armv8m, native)))]
+        let bits = EXC_RETURN_FIXED;
+        #[cfg(any(armv8m, native))]
+        let bits = EXC_RETURN_FIXED | EXC_RETURN_DCRS;
+
+        let mut exc_return = ExcReturn { bits };
+        exc_return.set(EXC_RETURN_MODE, mode == Mode::Thread);
+        exc_return.set(EXC_RETURN_SPSEL, stack == Spsel::Psp);
+        exc_return.set(EXC_RETURN_FTYPE, frame_type == FrameType::Basic);
+
+        Self::from_bits(exc_return.bits)
+    }
+
+    /// Creates an `ExcReturn` from raw bits.
+    ///
+    /// Returns `None` if `bits` is not a valid `EXC_RETURN` value for this architecture: the
+    /// prefix must be `0xFF`, the reserved bits must hold their fixed value, and Handler mode can
+    /// only return to the main stack. On ARMv6-M the frame is always a basic one.
+    #[inline]
+    pub fn from_bits(bits: u32) -> Option<Self> {
+        let exc_return = ExcReturn { bits };
+
+        if bits & !EXC_RETURN_FIELDS != EXC_RETURN_FIXED
+            || (exc_return.mode() == Mode::Handler && exc_return.stack() == Spsel::Psp)
+            || (cfg!(armv6m) && exc_return.frame_type() == FrameType::Extended)
+        {
+            None
+        } else {
+            Some(exc_return)
+        }
+    }
+
+    /// Reads the `EXC_RETURN` value from the Link Register, with [`register::lr::read`].
+    ///
+    /// This is only valid as the first operation of an exception handler: any function call made
+    /// before, including in debug builds the ones that are not inlined, overwrites the Link
+    /// Register. Returns `None` if the Link Register does not hold a valid `EXC_RETURN` value.
+    ///
+    /// [`register::lr::read`]: crate::register::lr::read
+    #[cfg(cortex_m)]
+    #[inline(always)]
+    pub fn from_lr() -> Option<Self> {
+        Self::from_bits(crate::register::lr::read())
+    }
+
+    /// Returns the contents of the value as raw bits
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    #[inline]
+    fn set(&mut self, mask: u32, value: bool) {
+        if value {
+            self.bits |= mask;
+        } else {
+            self.bits &= !mask;
+        }
+    }
+
+    /// Mode to return to (Mode)
+    #[inline]
+    pub fn mode(self) -> Mode {
+        if self.bits & EXC_RETURN_MODE != 0 {
+            Mode::Thread
+        } else {
+            Mode::Handler
+        }
+    }
+
+    /// Stack the frame was pushed on, and that is restored on return (SPSEL)
+    #[inline]
+    pub fn stack(self) -> Spsel {
+        if self.bits & EXC_RETURN_SPSEL != 0 {
+            Spsel::Psp
+        } else {
+            Spsel::Msp
+        }
+    }
+
+    /// Type of the stacked frame (FType)
+    #[inline]
+    pub fn frame_type(self) -> FrameType {
+        if self.bits & EXC_RETURN_FTYPE != 0 {
+            FrameType::Basic
+        } else {
+            FrameType::Extended
+        }
+    }
+
+    /// Security state the exception was taken to (ES)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn exception_security(self) -> SecurityState {
+        if self.bits & EXC_RETURN_ES != 0 {
+            SecurityState::Secure
+        } else {
+            SecurityState::NonSecure
+        }
+    }
+
+    /// Security state of the stack the frame was pushed on, that is the security state that was
+    /// interrupted (S)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn stack_security(self) -> SecurityState {
+        if self.bits & EXC_RETURN_S != 0 {
+            SecurityState::Secure
+        } else {
+            SecurityState::NonSecure
+        }
+    }
+
+    /// Whether the default callee register stacking rules were followed (DCRS)
+    ///
+    /// When `false`, the callee-saved registers were stacked as an [`AdditionalStateContext`]
+    /// below the exception frame.
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn default_callee_stacking(self) -> bool {
+        self.bits & EXC_RETURN_DCRS != 0
+    }
+
+    /// Sets the security state the exception was taken to (ES).
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_exception_security(&mut self, state: SecurityState) {
+        self.set(EXC_RETURN_ES, state == SecurityState::Secure)
+    }
+
+    /// Sets the security state of the stack to return to (S).
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_stack_security(&mut self, state: SecurityState) {
+        self.set(EXC_RETURN_S, state == SecurityState::Secure)
+    }
+
+    /// Sets whether the default callee register stacking rules apply (DCRS).
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_default_callee_stacking(&mut self, default: bool) {
+        self.set(EXC_RETURN_DCRS, default)
+    }
+}
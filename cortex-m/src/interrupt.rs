@@ -2,6 +2,7 @@
 
 #[cfg(cortex_m)]
 use core::arch::asm;
+use core::cell::{Ref, RefCell, RefMut, UnsafeCell};
 #[cfg(cortex_m)]
 use core::sync::atomic::{compiler_fence, Ordering};
 
@@ -52,11 +53,156 @@ pub unsafe fn enable() {
     asm!("cpsie i", options(nomem, nostack, preserves_flags));
 }
 
+/// Critical section token
+///
+/// An instance of this type indicates that the current core is executing code within a critical
+/// section, with interrupts disabled. It is passed by [`free`] to its closure and is needed to
+/// access the contents of a [`Mutex`].
+pub struct CriticalSection {
+    _0: (),
+}
+
+impl CriticalSection {
+    /// Creates a critical section token
+    ///
+    /// # Safety
+    ///
+    /// This must only be called from code that runs with interrupts disabled, and the token must
+    /// not outlive that section.
+    #[inline(always)]
+    pub unsafe fn new() -> Self {
+        CriticalSection { _0: () }
+    }
+}
+
+/// A "mutex" based on critical sections
+///
+/// The data can only be accessed from a critical section, which makes it safe to share between
+/// Thread mode and interrupt handlers on a single core system.
+///
+/// # Example
+///
+/// ``` no_run
+/// use core::cell::RefCell;
+/// use cortex_m::interrupt::{self, Mutex};
+///
+/// static COUNTER: Mutex<RefCell<u32>> = Mutex::new(RefCell::new(0));
+///
+/// fn increment() {
+///     interrupt::free(|cs| *COUNTER.borrow_ref_mut(cs) += 1);
+/// }
+/// ```
+pub struct Mutex<T> {
+    inner: UnsafeCell<T>,
+}
+
+impl<T> Mutex<T> {
+    /// Creates a new mutex
+    #[inline]
+    pub const fn new(value: T) -> Self {
+        Mutex {
+            inner: UnsafeCell::new(value),
+        }
+    }
+
+    /// Borrows the data for the duration of the critical section
+    #[inline]
+    pub fn borrow<'cs>(&'cs self, _cs: &'cs CriticalSection) -> &'cs T {
+        unsafe { &*self.inner.get() }
+    }
+
+    /// Returns a mutable reference to the data
+    ///
+    /// No critical section is needed since the mutex is mutably borrowed.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.inner.get_mut()
+    }
+
+    /// Unwraps the contained value, consuming the mutex
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.inner.into_inner()
+    }
+}
+
+impl<T> Mutex<RefCell<T>> {
+    /// Borrows the data immutably for the duration of the critical section
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data is currently mutably borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn borrow_ref<'cs>(&'cs self, cs: &'cs CriticalSection) -> Ref<'cs, T> {
+        self.borrow(cs).borrow()
+    }
+
+    /// Borrows the data mutably for the duration of the critical section
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data is currently borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn borrow_ref_mut<'cs>(&'cs self, cs: &'cs CriticalSection) -> RefMut<'cs, T> {
+        self.borrow(cs).borrow_mut()
+    }
+
+    /// Replaces the data with `value`, returning the previous value
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data is currently borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn replace(&self, cs: &CriticalSection, value: T) -> T {
+        self.borrow(cs).replace(value)
+    }
+
+    /// Replaces the data with the value returned by `f`, which is given a mutable reference to
+    /// the current value, and returns the previous value
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data is currently borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn replace_with<F>(&self, cs: &CriticalSection, f: F) -> T
+    where
+        F: FnOnce(&mut T) -> T,
+    {
+        self.borrow(cs).replace_with(f)
+    }
+
+    /// Takes the data, leaving `Default::default()` in its place
+    ///
+    /// # Panics
+    ///
+    /// Panics if the data is currently borrowed.
+    #[inline]
+    #[track_caller]
+    pub fn take(&self, cs: &CriticalSection) -> T
+    where
+        T: Default,
+    {
+        self.borrow(cs).take()
+    }
+}
+
+// NOTE A `Mutex` can be used as a channel so the protected data must be `Send`
+// to prevent sending non-Sendable stuff (e.g. access tokens) across different
+// execution contexts (e.g. interrupts)
+unsafe impl<T> Sync for Mutex<T> where T: Send {}
+
 /// Execute closure `f` with interrupts disabled in the current core.
 ///
+/// The closure is given a [`CriticalSection`] token, which gives access to the data of the
+/// [`Mutex`]es.
+///
 /// This method does not synchronise multiple cores and may disable required
 /// interrupts on some platforms; see the `critical-section` crate for a cross-platform
-/// way to enter a critical section which provides a `CriticalSection` token.
+/// way to enter a critical section which provides a `critical_section::CriticalSection` token.
 ///
 /// This crate provides an implementation for `critical-section` suitable for single-core systems,
 /// based on disabling all interrupts. It can be enabled with the `critical-section-single-core` feature.
@@ -64,14 +210,14 @@ pub unsafe fn enable() {
 #[inline]
 pub fn free<F, R>(f: F) -> R
 where
-    F: FnOnce() -> R,
+    F: FnOnce(&CriticalSection) -> R,
 {
     let primask = crate::register::primask::read();
 
     // disable interrupts
     disable();
 
-    let r = f();
+    let r = f(unsafe { &CriticalSection::new() });
 
     // If the interrupts were active before our `disable` call, then re-enable
     // them. Otherwise, keep them disabled
@@ -89,7 +235,7 @@ where
 #[inline]
 pub fn free<F, R>(_: F) -> R
 where
-    F: FnOnce() -> R,
+    F: FnOnce(&CriticalSection) -> R,
 {
     panic!("cortex_m::interrupt::free() is only functional on cortex-m platforms");
 }
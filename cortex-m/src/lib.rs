@@ -19,6 +19,24 @@
 //! or critical sections are managed as part of an RTOS. In these cases, you should use
 //! a target-specific implementation instead, typically provided by a HAL or RTOS crate.
 //!
+//! ## `critical-section-basepri`
+//!
+//! This feature enables a [`critical-section`](https://github.com/rust-embedded/critical-section)
+//! implementation for single-core targets that raises BASEPRI instead of disabling all interrupts:
+//! the interrupts with a higher priority than the ceiling keep running during critical sections.
+//! The ceiling is the raw BASEPRI value given by the `CORTEX_M_BASEPRI_CEILING` environment
+//! variable at build time, for example `CORTEX_M_BASEPRI_CEILING=0x40`.
+//!
+//! BASEPRI only implements the most significant priority bits of the core, the other bits read
+//! as zero. The ceiling must have at least one of the implemented bits set, and should only use
+//! those: with 3 priority bits, `0x20` to `0xE0` are valid ceilings while any value between
+//! `0x01` and `0x1F` reads back as zero. Entering a critical section panics if BASEPRI is still
+//! zero after it was raised to the ceiling.
+//!
+//! The interrupts that are not masked by the ceiling must not enter critical sections, nor access
+//! the data they protect. BASEPRI is not implemented on ARMv6-M and ARMv8-M Baseline, the feature
+//! can not be used there nor together with `critical-section-single-core`.
+//!
 //! ## `cm7-r0p1`
 //!
 //! This feature enables workarounds for errata found on Cortex-M7 chips with revision r0p1. Some
@@ -59,10 +77,28 @@ mod macros;
 pub mod asm;
 #[cfg(armv8m)]
 pub mod cmse;
+#[cfg(all(
+    cortex_m,
+    any(
+        feature = "critical-section-single-core",
+        feature = "critical-section-basepri"
+    )
+))]
+mod critical_section;
 pub mod delay;
+pub mod exception;
 pub mod interrupt;
 #[cfg(all(not(armv6m), not(armv8m_base)))]
 pub mod itm;
 pub mod peripheral;
 pub mod register;
+#[cfg(cortex_m)]
+pub mod resource;
 pub mod vtypes;
+
+/// Used to reexport items for use in macros. Do not use directly.
+/// Not covered by semver guarantees.
+#[doc(hidden)]
+pub mod _export {
+    pub use critical_section;
+}
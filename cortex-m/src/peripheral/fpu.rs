@@ -2,6 +2,8 @@
 //!
 //! *NOTE* Available only on targets with a Floating Point Unit (FPU) extension.
 
+use crate::peripheral::FPU;
+use crate::register::fpscr::RMode;
 use crate::vtypes::{RO, RW};
 
 /// Register block
@@ -17,3 +19,506 @@ pub struct RegisterBlock {
     /// Media and FP Feature
     pub mvfr: [RO<u32>; 3],
 }
+
+const FPCCR_LSPACT: u32 = 1 << 0;
+const FPCCR_USER: u32 = 1 << 1;
+#[cfg(any(armv8m, native))]
+const FPCCR_S: u32 = 1 << 2;
+const FPCCR_THREAD: u32 = 1 << 3;
+const FPCCR_HFRDY: u32 = 1 << 4;
+const FPCCR_MMRDY: u32 = 1 << 5;
+const FPCCR_BFRDY: u32 = 1 << 6;
+#[cfg(any(armv8m, native))]
+const FPCCR_SFRDY: u32 = 1 << 7;
+const FPCCR_MONRDY: u32 = 1 << 8;
+#[cfg(any(armv8m, native))]
+const FPCCR_SPLIMVIOL: u32 = 1 << 9;
+#[cfg(any(armv8m, native))]
+const FPCCR_TS: u32 = 1 << 26;
+#[cfg(any(armv8m, native))]
+const FPCCR_CLRONRETS: u32 = 1 << 27;
+#[cfg(any(armv8m, native))]
+const FPCCR_CLRONRET: u32 = 1 << 28;
+#[cfg(any(armv8m, native))]
+const FPCCR_LSPENS: u32 = 1 << 29;
+const FPCCR_LSPEN: u32 = 1 << 30;
+const FPCCR_ASPEN: u32 = 1 << 31;
+
+const FPDSCR_AHP: u32 = 1 << 26;
+const FPDSCR_DN: u32 = 1 << 25;
+const FPDSCR_FZ: u32 = 1 << 24;
+const FPDSCR_RMODE_POS: u32 = 22;
+const FPDSCR_RMODE_MASK: u32 = 0b11 << FPDSCR_RMODE_POS;
+
+/// Floating-Point Context Control Register
+///
+/// Controls how the floating-point context is preserved on exception entry. The `*RDY` and
+/// `LSPACT` bits describe a pending lazy preservation and are set by the hardware, hence only
+/// getters are provided for them.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fpccr {
+    bits: u32,
+}
+
+impl Fpccr {
+    /// Creates a `Fpccr` value from raw bits.
+    #[inline]
+    pub fn from_bits(bits: u32) -> Self {
+        Self { bits }
+    }
+
+    /// Returns the contents of the register as raw bits
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    #[inline]
+    fn bit(self, mask: u32) -> bool {
+        self.bits & mask != 0
+    }
+
+    #[inline]
+    fn set_bit(&mut self, mask: u32, value: bool) {
+        if value {
+            self.bits |= mask;
+        } else {
+            self.bits &= !mask;
+        }
+    }
+
+    /// Whether the FP context is automatically saved on exception entry (ASPEN)
+    ///
+    /// When set, executing a floating-point instruction sets `CONTROL.FPCA` and the exception
+    /// entry reserves space for the FP registers in the stack frame.
+    #[inline]
+    pub fn aspen(self) -> bool {
+        self.bit(FPCCR_ASPEN)
+    }
+
+    /// Sets the ASPEN value.
+    #[inline]
+    pub fn set_aspen(&mut self, value: bool) {
+        self.set_bit(FPCCR_ASPEN, value);
+    }
+
+    /// Whether the saving of the FP registers is deferred until they are used (LSPEN)
+    #[inline]
+    pub fn lspen(self) -> bool {
+        self.bit(FPCCR_LSPEN)
+    }
+
+    /// Sets the LSPEN value.
+    #[inline]
+    pub fn set_lspen(&mut self, value: bool) {
+        self.set_bit(FPCCR_LSPEN, value);
+    }
+
+    /// Whether a lazy state preservation is active (LSPACT)
+    ///
+    /// Space has been reserved on the stack for the FP context but the registers have not
+    /// been saved yet.
+    #[inline]
+    pub fn lspact(self) -> bool {
+        self.bit(FPCCR_LSPACT)
+    }
+
+    /// Whether the FP stack frame was allocated in unprivileged mode (USER)
+    #[inline]
+    pub fn user(self) -> bool {
+        self.bit(FPCCR_USER)
+    }
+
+    /// Whether the FP stack frame was allocated in Thread mode (THREAD)
+    #[inline]
+    pub fn thread(self) -> bool {
+        self.bit(FPCCR_THREAD)
+    }
+
+    /// Whether the HardFault handler could be pended when the FP stack frame was allocated
+    /// (HFRDY)
+    #[inline]
+    pub fn hfrdy(self) -> bool {
+        self.bit(FPCCR_HFRDY)
+    }
+
+    /// Whether the MemManage handler could be pended when the FP stack frame was allocated
+    /// (MMRDY)
+    #[inline]
+    pub fn mmrdy(self) -> bool {
+        self.bit(FPCCR_MMRDY)
+    }
+
+    /// Whether the BusFault handler could be pended when the FP stack frame was allocated
+    /// (BFRDY)
+    #[inline]
+    pub fn bfrdy(self) -> bool {
+        self.bit(FPCCR_BFRDY)
+    }
+
+    /// Whether the DebugMonitor exception could be pended when the FP stack frame was
+    /// allocated (MONRDY)
+    #[inline]
+    pub fn monrdy(self) -> bool {
+        self.bit(FPCCR_MONRDY)
+    }
+
+    /// Whether the FP stack frame belongs to the Secure state (S)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn s(self) -> bool {
+        self.bit(FPCCR_S)
+    }
+
+    /// Whether the SecureFault handler could be pended when the FP stack frame was allocated
+    /// (SFRDY)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn sfrdy(self) -> bool {
+        self.bit(FPCCR_SFRDY)
+    }
+
+    /// Whether a stack limit violation happened while the FP stack frame was allocated
+    /// (SPLIMVIOL)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn splimviol(self) -> bool {
+        self.bit(FPCCR_SPLIMVIOL)
+    }
+
+    /// Whether the FP registers are treated as Secure (TS)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn ts(self) -> bool {
+        self.bit(FPCCR_TS)
+    }
+
+    /// Sets the TS value.
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_ts(&mut self, value: bool) {
+        self.set_bit(FPCCR_TS, value);
+    }
+
+    /// Whether CLRONRET can only be modified from the Secure state (CLRONRETS)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn clronrets(self) -> bool {
+        self.bit(FPCCR_CLRONRETS)
+    }
+
+    /// Sets the CLRONRETS value.
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_clronrets(&mut self, value: bool) {
+        self.set_bit(FPCCR_CLRONRETS, value);
+    }
+
+    /// Whether the caller-saved FP registers are cleared on exception return (CLRONRET)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn clronret(self) -> bool {
+        self.bit(FPCCR_CLRONRET)
+    }
+
+    /// Sets the CLRONRET value.
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_clronret(&mut self, value: bool) {
+        self.set_bit(FPCCR_CLRONRET, value);
+    }
+
+    /// Whether LSPEN can only be modified from the Secure state (LSPENS)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn lspens(self) -> bool {
+        self.bit(FPCCR_LSPENS)
+    }
+
+    /// Sets the LSPENS value.
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_lspens(&mut self, value: bool) {
+        self.set_bit(FPCCR_LSPENS, value);
+    }
+}
+
+/// Floating-Point Default Status Control Register
+///
+/// Holds the default `FPSCR` configuration given to a new floating-point context.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Fpdscr {
+    bits: u32,
+}
+
+impl Fpdscr {
+    /// Creates a `Fpdscr` value from raw bits.
+    #[inline]
+    pub fn from_bits(bits: u32) -> Self {
+        Self { bits }
+    }
+
+    /// Returns the contents of the register as raw bits
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    #[inline]
+    fn set_bit(&mut self, mask: u32, value: bool) {
+        if value {
+            self.bits |= mask;
+        } else {
+            self.bits &= !mask;
+        }
+    }
+
+    /// Default value of the Alternative Half-Precision control bit (AHP)
+    #[inline]
+    pub fn ahp(self) -> bool {
+        self.bits & FPDSCR_AHP != 0
+    }
+
+    /// Sets the default AHP value.
+    #[inline]
+    pub fn set_ahp(&mut self, value: bool) {
+        self.set_bit(FPDSCR_AHP, value);
+    }
+
+    /// Default value of the Default NaN mode control bit (DN)
+    #[inline]
+    pub fn dn(self) -> bool {
+        self.bits & FPDSCR_DN != 0
+    }
+
+    /// Sets the default DN value.
+    #[inline]
+    pub fn set_dn(&mut self, value: bool) {
+        self.set_bit(FPDSCR_DN, value);
+    }
+
+    /// Default value of the Flush-to-zero mode control bit (FZ)
+    #[inline]
+    pub fn fz(self) -> bool {
+        self.bits & FPDSCR_FZ != 0
+    }
+
+    /// Sets the default FZ value.
+    #[inline]
+    pub fn set_fz(&mut self, value: bool) {
+        self.set_bit(FPDSCR_FZ, value);
+    }
+
+    /// Default rounding mode (RMode)
+    #[inline]
+    pub fn rmode(self) -> RMode {
+        match (self.bits & FPDSCR_RMODE_MASK) >> FPDSCR_RMODE_POS {
+            0 => RMode::Nearest,
+            1 => RMode::PlusInfinity,
+            2 => RMode::MinusInfinity,
+            _ => RMode::Zero,
+        }
+    }
+
+    /// Sets the default rounding mode.
+    #[inline]
+    pub fn set_rmode(&mut self, rmode: RMode) {
+        let value = match rmode {
+            RMode::Nearest => 0,
+            RMode::PlusInfinity => 1,
+            RMode::MinusInfinity => 2,
+            RMode::Zero => 3,
+        };
+        self.bits = (self.bits & !FPDSCR_RMODE_MASK) | (value << FPDSCR_RMODE_POS);
+    }
+}
+
+/// Precision implemented by the FPU
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FpuVariant {
+    /// Single-precision only (FPv4-SP, FPv5-SP)
+    SinglePrecision,
+    /// Single and double-precision (FPv5-DP)
+    DoublePrecision,
+}
+
+/// Media and VFP Feature Registers
+///
+/// Describes the floating-point features implemented by the processor.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Mvfr {
+    words: [u32; 3],
+}
+
+impl Mvfr {
+    /// Creates a `Mvfr` value from the raw contents of MVFR0, MVFR1 and MVFR2.
+    #[inline]
+    pub fn from_words(words: [u32; 3]) -> Self {
+        Self { words }
+    }
+
+    /// Returns the raw contents of MVFR0, MVFR1 and MVFR2.
+    #[inline]
+    pub fn words(self) -> [u32; 3] {
+        self.words
+    }
+
+    #[inline]
+    fn field(self, register: usize, pos: u32) -> u32 {
+        (self.words[register] >> pos) & 0xF
+    }
+
+    /// Whether single-precision operations are supported (MVFR0.SP)
+    #[inline]
+    pub fn single_precision(self) -> bool {
+        self.field(0, 4) != 0
+    }
+
+    /// Whether double-precision operations are supported (MVFR0.DP)
+    #[inline]
+    pub fn double_precision(self) -> bool {
+        self.field(0, 8) != 0
+    }
+
+    /// Whether the divide instructions are supported (MVFR0.Divide)
+    #[inline]
+    pub fn divide(self) -> bool {
+        self.field(0, 16) != 0
+    }
+
+    /// Whether the square root instructions are supported (MVFR0.SquareRoot)
+    #[inline]
+    pub fn square_root(self) -> bool {
+        self.field(0, 20) != 0
+    }
+
+    /// Whether the fused multiply accumulate instructions are supported (MVFR1.FMAC)
+    #[inline]
+    pub fn fused_mac(self) -> bool {
+        self.field(1, 28) != 0
+    }
+
+    /// Returns the precision implemented by the FPU, or `None` if no floating-point
+    /// operations are supported.
+    #[inline]
+    pub fn variant(self) -> Option<FpuVariant> {
+        if self.double_precision() {
+            Some(FpuVariant::DoublePrecision)
+        } else if self.single_precision() {
+            Some(FpuVariant::SinglePrecision)
+        } else {
+            None
+        }
+    }
+}
+
+impl FPU {
+    /// Reads the Floating-Point Context Control register
+    #[inline]
+    pub fn fpccr() -> Fpccr {
+        // NOTE(unsafe) atomic read with no side effects
+        Fpccr::from_bits(unsafe { (*Self::PTR).fpccr.read() })
+    }
+
+    /// Writes the Floating-Point Context Control register
+    ///
+    /// The write is followed by a `DSB` and an `ISB` so that the new configuration applies to
+    /// the following instructions.
+    ///
+    /// # Unsafety
+    ///
+    /// Changing ASPEN or LSPEN while a floating-point context is active, or writing the
+    /// status bits, can corrupt the FP state saved for a preempted context. The register
+    /// should only be changed at boot, before any floating-point instruction is executed.
+    #[inline]
+    pub unsafe fn set_fpccr(&mut self, fpccr: Fpccr) {
+        self.fpccr.write(fpccr.bits());
+        crate::asm::dsb();
+        crate::asm::isb();
+    }
+
+    /// Modifies the Floating-Point Context Control register
+    ///
+    /// See [`FPU::set_fpccr`].
+    ///
+    /// # Unsafety
+    ///
+    /// See [`FPU::set_fpccr`].
+    #[inline]
+    pub unsafe fn modify_fpccr<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Fpccr),
+    {
+        let mut fpccr = Self::fpccr();
+        f(&mut fpccr);
+        self.set_fpccr(fpccr);
+    }
+
+    /// Enables automatic and lazy preservation of the floating-point context
+    ///
+    /// This is the reset configuration of the FPCCR, which the application may have changed.
+    ///
+    /// # Unsafety
+    ///
+    /// See [`FPU::set_fpccr`].
+    #[inline]
+    pub unsafe fn enable_lazy_stacking(&mut self) {
+        self.modify_fpccr(|r| {
+            r.set_aspen(true);
+            r.set_lspen(true);
+        });
+    }
+
+    /// Reads the Floating-Point Context Address register
+    ///
+    /// This is the address of the space reserved on the stack for the FP registers of the
+    /// last lazy state preservation.
+    #[inline]
+    pub fn fpcar() -> u32 {
+        // NOTE(unsafe) atomic read with no side effects
+        unsafe { (*Self::PTR).fpcar.read() & !0b111 }
+    }
+
+    /// Reads the Floating-Point Default Status Control register
+    #[inline]
+    pub fn fpdscr() -> Fpdscr {
+        // NOTE(unsafe) atomic read with no side effects
+        Fpdscr::from_bits(unsafe { (*Self::PTR).fpdscr.read() })
+    }
+
+    /// Writes the Floating-Point Default Status Control register
+    ///
+    /// The new defaults apply to floating-point contexts created after the write.
+    #[inline]
+    pub fn set_fpdscr(&mut self, fpdscr: Fpdscr) {
+        // NOTE(unsafe) atomic write to a stateless register
+        unsafe { self.fpdscr.write(fpdscr.bits()) }
+    }
+
+    /// Modifies the Floating-Point Default Status Control register
+    #[inline]
+    pub fn modify_fpdscr<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Fpdscr),
+    {
+        let mut fpdscr = Self::fpdscr();
+        f(&mut fpdscr);
+        self.set_fpdscr(fpdscr);
+    }
+
+    /// Reads the Media and VFP Feature registers
+    #[inline]
+    pub fn mvfr() -> Mvfr {
+        // NOTE(unsafe) atomic reads with no side effects
+        let mvfr = unsafe { &(*Self::PTR).mvfr };
+        Mvfr::from_words([mvfr[0].read(), mvfr[1].read(), mvfr[2].read()])
+    }
+
+    /// Returns the precision implemented by the FPU
+    ///
+    /// See [`Mvfr::variant`].
+    #[inline]
+    pub fn variant() -> Option<FpuVariant> {
+        Self::mvfr().variant()
+    }
+}
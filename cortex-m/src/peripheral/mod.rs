@@ -87,6 +87,167 @@ pub mod tpiu;
 #[cfg(test)]
 mod test;
 
+/// Core peripherals
+#[allow(non_snake_case)]
+#[allow(clippy::manual_non_exhaustive)]
+pub struct Peripherals {
+    /// Cortex-M7 TCM and cache access control.
+    #[cfg(feature = "cm7")]
+    pub AC: AC,
+
+    /// Cache and branch predictor maintenance operations.
+    /// Not available on Armv6-M.
+    #[cfg(not(armv6m))]
+    pub CBP: CBP,
+
+    /// CPUID
+    pub CPUID: CPUID,
+
+    /// Debug Control Block
+    pub DCB: DCB,
+
+    /// Data Watchpoint and Trace unit
+    pub DWT: DWT,
+
+    /// Flash Patch and Breakpoint unit.
+    /// Not available on Armv6-M.
+    #[cfg(not(armv6m))]
+    pub FPB: FPB,
+
+    /// Floating Point Unit.
+    /// Only available on targets with a hardware FPU.
+    #[cfg(any(has_fpu, native))]
+    pub FPU: FPU,
+
+    /// Implementation Control Block.
+    ///
+    /// The name is from the v8-M spec, but the block existed in earlier
+    /// revisions, without a name.
+    pub ICB: ICB,
+
+    /// Instrumentation Trace Macrocell.
+    /// Not available on Armv6-M and Armv8-M Baseline.
+    #[cfg(all(not(armv6m), not(armv8m_base)))]
+    pub ITM: ITM,
+
+    /// Memory Protection Unit
+    pub MPU: MPU,
+
+    /// Nested Vector Interrupt Controller
+    pub NVIC: NVIC,
+
+    /// Security Attribution Unit.
+    /// Only available on Armv8-M.
+    #[cfg(armv8m)]
+    pub SAU: SAU,
+
+    /// System Control Block
+    pub SCB: SCB,
+
+    /// SysTick: System Timer
+    pub SYST: SYST,
+
+    /// Trace Port Interface Unit.
+    /// Not available on Armv6-M.
+    #[cfg(not(armv6m))]
+    pub TPIU: TPIU,
+
+    // Private field making `Peripherals` non-exhaustive. We don't use `#[non_exhaustive]` so that
+    // the struct can still be built in this crate with a struct expression.
+    _priv: (),
+}
+
+// NOTE `no_mangle` is used here to prevent linking different minor versions of this crate as that
+// would let you `take` the core peripherals more than once (one per minor version)
+#[no_mangle]
+static CORE_PERIPHERALS: () = ();
+
+/// Set to `true` when `take` or `steal` was called to make `Peripherals` a singleton.
+static mut TAKEN: bool = false;
+
+impl Peripherals {
+    /// Returns all the core peripherals *once*
+    ///
+    /// The check is done in a critical section, see the `critical-section` crate.
+    #[inline]
+    pub fn take() -> Option<Self> {
+        critical_section::with(|_| {
+            if unsafe { TAKEN } {
+                None
+            } else {
+                Some(unsafe { Peripherals::steal() })
+            }
+        })
+    }
+
+    /// Unchecked version of `Peripherals::take`
+    ///
+    /// # Safety
+    ///
+    /// Each of the returned peripherals must be used at most once: the peripherals must not be
+    /// stolen again while a previous instance is still in use.
+    #[inline]
+    pub unsafe fn steal() -> Self {
+        TAKEN = true;
+
+        Peripherals {
+            #[cfg(feature = "cm7")]
+            AC: AC {
+                _marker: PhantomData,
+            },
+            #[cfg(not(armv6m))]
+            CBP: CBP {
+                _marker: PhantomData,
+            },
+            CPUID: CPUID {
+                _marker: PhantomData,
+            },
+            DCB: DCB {
+                _marker: PhantomData,
+            },
+            DWT: DWT {
+                _marker: PhantomData,
+            },
+            #[cfg(not(armv6m))]
+            FPB: FPB {
+                _marker: PhantomData,
+            },
+            #[cfg(any(has_fpu, native))]
+            FPU: FPU {
+                _marker: PhantomData,
+            },
+            ICB: ICB {
+                _marker: PhantomData,
+            },
+            #[cfg(all(not(armv6m), not(armv8m_base)))]
+            ITM: ITM {
+                _marker: PhantomData,
+            },
+            MPU: MPU {
+                _marker: PhantomData,
+            },
+            NVIC: NVIC {
+                _marker: PhantomData,
+            },
+            #[cfg(armv8m)]
+            SAU: SAU {
+                _marker: PhantomData,
+            },
+            SCB: SCB {
+                _marker: PhantomData,
+            },
+            SYST: SYST {
+                _marker: PhantomData,
+            },
+            #[cfg(not(armv6m))]
+            TPIU: TPIU {
+                _marker: PhantomData,
+            },
+            _priv: (),
+        }
+    }
+}
+
 /// Access control
 #[cfg(feature = "cm7")]
 pub struct AC {
@@ -1,7 +1,15 @@
 //! Memory Protection Unit
 
+use crate::peripheral::MPU;
 use crate::vtypes::{RO, RW};
 
+#[cfg(armv8m)]
+use self::armv8::MPU_RLAR_ENABLE;
+#[cfg(armv8m)]
+pub use self::armv8::{
+    MemoryAttribute, MpuAccessPermission, MpuCachePolicy, MpuError, MpuRegion, MpuShareability,
+};
+
 /// Register block for ARMv7-M
 #[cfg(not(armv8m))]
 #[repr(C)]
@@ -63,3 +71,1229 @@ pub struct RegisterBlock {
     /// Memory Attribute Indirection register 0 and 1
     pub mair: [RW<u32>; 2],
 }
+
+const MPU_TYPE_DREGION_SHIFT: u32 = 8;
+
+#[cfg(not(armv8m))]
+const MPU_RBAR_ADDR_MASK: u32 = !0x1F;
+#[cfg(not(armv8m))]
+const MPU_RBAR_VALID: u32 = 1 << 4;
+
+#[cfg(not(armv8m))]
+const MPU_RASR_ENABLE: u32 = 1 << 0;
+#[cfg(not(armv8m))]
+const MPU_RASR_SIZE_SHIFT: u32 = 1;
+#[cfg(not(armv8m))]
+const MPU_RASR_SRD_SHIFT: u32 = 8;
+#[cfg(not(armv8m))]
+const MPU_RASR_B: u32 = 1 << 16;
+#[cfg(not(armv8m))]
+const MPU_RASR_C: u32 = 1 << 17;
+#[cfg(not(armv8m))]
+const MPU_RASR_S: u32 = 1 << 18;
+#[cfg(not(armv8m))]
+const MPU_RASR_TEX_SHIFT: u32 = 19;
+#[cfg(not(armv8m))]
+const MPU_RASR_AP_SHIFT: u32 = 24;
+#[cfg(not(armv8m))]
+const MPU_RASR_XN: u32 = 1 << 28;
+
+/// Access permissions of an ARMv7-M MPU region (the `AP` field of `RASR`).
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MpuAccessPermission {
+    /// No access, from any privilege level
+    NoAccess,
+    /// Read/write from privileged code only
+    PrivilegedReadWrite,
+    /// Read/write from privileged code, read-only from unprivileged code
+    PrivilegedReadWriteUnprivilegedReadOnly,
+    /// Read/write from any privilege level
+    ReadWrite,
+    /// Read-only from privileged code only
+    PrivilegedReadOnly,
+    /// Read-only from any privilege level
+    ReadOnly,
+}
+
+/// Cache policy of one level (inner or outer) of a Normal memory region.
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MpuCachePolicy {
+    /// Non-cacheable
+    NonCacheable,
+    /// Write-back, write and read allocate
+    WriteBackWriteAllocate,
+    /// Write-through, no write allocate
+    WriteThrough,
+    /// Write-back, no write allocate
+    WriteBack,
+}
+
+/// Memory type of an ARMv7-M MPU region (the `TEX`, `C`, `B` and `S` fields of `RASR`).
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MpuMemoryType {
+    /// Strongly-ordered memory, always shareable
+    StronglyOrdered,
+    /// Device memory
+    Device {
+        /// Whether the region is shareable
+        shareable: bool,
+    },
+    /// Normal memory
+    Normal {
+        /// Outer cache policy
+        outer: MpuCachePolicy,
+        /// Inner cache policy
+        inner: MpuCachePolicy,
+        /// Whether the region is shareable
+        shareable: bool,
+    },
+}
+
+/// Description of an ARMv7-M MPU region.
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MpuRegion {
+    /// First address of the region, it must be aligned to the size of the region.
+    pub base_address: u32,
+    /// Size of the region as a power of two: the region spans `2^size_log2` bytes. It must be
+    /// between 5 (32 bytes) and 32 (4 GiB).
+    pub size_log2: u8,
+    /// Access permissions of the region.
+    pub access: MpuAccessPermission,
+    /// Memory type and cacheability of the region.
+    pub memory_type: MpuMemoryType,
+    /// Whether instruction fetches from the region are forbidden.
+    pub execute_never: bool,
+    /// Subregion Disable mask: setting bit `n` disables the `n`-th eighth of the region.
+    /// Subregions are only supported by regions of 256 bytes or more, so this must be zero for
+    /// smaller regions.
+    pub subregion_disable: u8,
+}
+
+/// Possible error values returned by the MPU methods.
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MpuError {
+    /// The region number parameter to set or get a region must be between 0 and
+    /// region_numbers() - 1.
+    RegionNumberTooBig,
+    /// The base address of a region must be aligned to the size of the region.
+    WrongBaseAddress,
+    /// The size of a region must be between 32 bytes and 4 GiB.
+    WrongSize,
+    /// Subregions can only be disabled in regions of 256 bytes or more.
+    SubregionDisableNotSupported,
+    /// The attributes of the region can not be represented on this core (ARMv6-M does not
+    /// implement the `TEX` field), or a region read back from the MPU uses a reserved encoding.
+    UnsupportedAttributes,
+}
+
+#[cfg(not(armv8m))]
+impl MpuCachePolicy {
+    #[inline]
+    fn bits(self) -> u32 {
+        match self {
+            MpuCachePolicy::NonCacheable => 0b00,
+            MpuCachePolicy::WriteBackWriteAllocate => 0b01,
+            MpuCachePolicy::WriteThrough => 0b10,
+            MpuCachePolicy::WriteBack => 0b11,
+        }
+    }
+
+    #[inline]
+    fn from_bits(bits: u32) -> Self {
+        match bits & 0b11 {
+            0b00 => MpuCachePolicy::NonCacheable,
+            0b01 => MpuCachePolicy::WriteBackWriteAllocate,
+            0b10 => MpuCachePolicy::WriteThrough,
+            _ => MpuCachePolicy::WriteBack,
+        }
+    }
+}
+
+#[cfg(not(armv8m))]
+impl MpuRegion {
+    /// Returns the `RBAR` and `RASR` values describing this region.
+    ///
+    /// The `VALID` and `REGION` fields of `RBAR` are left to zero.
+    pub(crate) fn encode(&self) -> Result<(u32, u32), MpuError> {
+        if !(5..=32).contains(&self.size_log2) {
+            return Err(MpuError::WrongSize);
+        }
+
+        let size_mask = ((1u64 << self.size_log2) - 1) as u32;
+        if self.base_address & size_mask != 0 {
+            return Err(MpuError::WrongBaseAddress);
+        }
+
+        if self.size_log2 < 8 && self.subregion_disable != 0 {
+            return Err(MpuError::SubregionDisableNotSupported);
+        }
+
+        let ap = match self.access {
+            MpuAccessPermission::NoAccess => 0b000,
+            MpuAccessPermission::PrivilegedReadWrite => 0b001,
+            MpuAccessPermission::PrivilegedReadWriteUnprivilegedReadOnly => 0b010,
+            MpuAccessPermission::ReadWrite => 0b011,
+            MpuAccessPermission::PrivilegedReadOnly => 0b101,
+            MpuAccessPermission::ReadOnly => 0b110,
+        };
+
+        // (TEX, C, B, S)
+        let (tex, c, b, s) = match self.memory_type {
+            MpuMemoryType::StronglyOrdered => (0b000, false, false, false),
+            MpuMemoryType::Device { shareable: true } => (0b000, false, true, false),
+            MpuMemoryType::Device { shareable: false } => (0b010, false, false, false),
+            MpuMemoryType::Normal {
+                outer,
+                inner,
+                shareable,
+            } => {
+                // Use the short encodings when both levels share the same policy, they are the
+                // only ones available on ARMv6-M.
+                match (outer == inner, inner) {
+                    (true, MpuCachePolicy::WriteThrough) => (0b000, true, false, shareable),
+                    (true, MpuCachePolicy::WriteBack) => (0b000, true, true, shareable),
+                    (true, MpuCachePolicy::NonCacheable) => (0b001, false, false, shareable),
+                    (true, MpuCachePolicy::WriteBackWriteAllocate) => {
+                        (0b001, true, true, shareable)
+                    }
+                    (false, _) => (
+                        0b100 | outer.bits(),
+                        inner.bits() & 0b10 != 0,
+                        inner.bits() & 0b01 != 0,
+                        shareable,
+                    ),
+                }
+            }
+        };
+
+        // ARMv6-M does not implement the TEX field.
+        if cfg!(armv6m) && tex != 0 {
+            return Err(MpuError::UnsupportedAttributes);
+        }
+
+        let mut rasr = MPU_RASR_ENABLE
+            | (u32::from(self.size_log2 - 1) << MPU_RASR_SIZE_SHIFT)
+            | (u32::from(self.subregion_disable) << MPU_RASR_SRD_SHIFT)
+            | (tex << MPU_RASR_TEX_SHIFT)
+            | (ap << MPU_RASR_AP_SHIFT);
+        if b {
+            rasr |= MPU_RASR_B;
+        }
+        if c {
+            rasr |= MPU_RASR_C;
+        }
+        if s {
+            rasr |= MPU_RASR_S;
+        }
+        if self.execute_never {
+            rasr |= MPU_RASR_XN;
+        }
+
+        Ok((self.base_address, rasr))
+    }
+
+    /// Decodes the `RBAR` and `RASR` values of a region.
+    ///
+    /// Returns `Ok(None)` if the region is disabled.
+    pub(crate) fn decode(rbar: u32, rasr: u32) -> Result<Option<Self>, MpuError> {
+        if rasr & MPU_RASR_ENABLE == 0 {
+            return Ok(None);
+        }
+
+        let access = match (rasr >> MPU_RASR_AP_SHIFT) & 0b111 {
+            0b000 => MpuAccessPermission::NoAccess,
+            0b001 => MpuAccessPermission::PrivilegedReadWrite,
+            0b010 => MpuAccessPermission::PrivilegedReadWriteUnprivilegedReadOnly,
+            0b011 => MpuAccessPermission::ReadWrite,
+            0b101 => MpuAccessPermission::PrivilegedReadOnly,
+            0b110 | 0b111 => MpuAccessPermission::ReadOnly,
+            _ => return Err(MpuError::UnsupportedAttributes),
+        };
+
+        let tex = (rasr >> MPU_RASR_TEX_SHIFT) & 0b111;
+        let c = rasr & MPU_RASR_C != 0;
+        let b = rasr & MPU_RASR_B != 0;
+        let shareable = rasr & MPU_RASR_S != 0;
+        let normal = |policy| MpuMemoryType::Normal {
+            outer: policy,
+            inner: policy,
+            shareable,
+        };
+
+        let memory_type = match (tex, c, b) {
+            (0b000, false, false) => MpuMemoryType::StronglyOrdered,
+            (0b000, false, true) => MpuMemoryType::Device { shareable: true },
+            (0b000, true, false) => normal(MpuCachePolicy::WriteThrough),
+            (0b000, true, true) => normal(MpuCachePolicy::WriteBack),
+            (0b001, false, false) => normal(MpuCachePolicy::NonCacheable),
+            (0b001, true, true) => normal(MpuCachePolicy::WriteBackWriteAllocate),
+            (0b010, false, false) => MpuMemoryType::Device { shareable: false },
+            (tex, c, b) if tex & 0b100 != 0 => MpuMemoryType::Normal {
+                outer: MpuCachePolicy::from_bits(tex),
+                inner: MpuCachePolicy::from_bits((u32::from(c) << 1) | u32::from(b)),
+                shareable,
+            },
+            _ => return Err(MpuError::UnsupportedAttributes),
+        };
+
+        let size_log2 = ((rasr >> MPU_RASR_SIZE_SHIFT) & 0x1F) as u8 + 1;
+        if size_log2 < 5 {
+            return Err(MpuError::WrongSize);
+        }
+        let size_mask = ((1u64 << size_log2) - 1) as u32;
+
+        Ok(Some(MpuRegion {
+            base_address: rbar & MPU_RBAR_ADDR_MASK & !size_mask,
+            size_log2,
+            access,
+            memory_type,
+            execute_never: rasr & MPU_RASR_XN != 0,
+            subregion_disable: (rasr >> MPU_RASR_SRD_SHIFT) as u8,
+        }))
+    }
+}
+
+impl MPU {
+    /// Get the number of implemented MPU regions.
+    ///
+    /// A value of zero indicates that the MPU is not implemented.
+    #[inline]
+    pub fn region_numbers(&self) -> u8 {
+        (self._type.read() >> MPU_TYPE_DREGION_SHIFT) as u8
+    }
+}
+
+#[cfg(not(armv8m))]
+impl MPU {
+    /// Set an MPU region to a region number and enable it.
+    /// MPU regions must be a power of two in size, between 32 bytes and 4 GiB, and their base
+    /// address must be aligned to their size. Subregions can only be disabled in regions of 256
+    /// bytes or more.
+    /// The region number must be valid.
+    /// This function is executed with interrupts disabled to prevent having inconsistent results.
+    #[inline]
+    pub fn set_region(&mut self, region_number: u8, region: MpuRegion) -> Result<(), MpuError> {
+        if region_number >= self.region_numbers() {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        let (rbar, rasr) = region.encode()?;
+
+        crate::interrupt::free(|_| unsafe {
+            self.rnr.write(region_number.into());
+            self.rbar.write(rbar);
+            self.rasr.write(rasr);
+        });
+
+        crate::asm::dsb();
+        crate::asm::isb();
+
+        Ok(())
+    }
+
+    /// Get a region from the MPU.
+    /// Returns `Ok(None)` if the region is disabled.
+    /// The region number must be valid.
+    /// This function is executed with interrupts disabled to prevent having inconsistent results.
+    #[inline]
+    pub fn get_region(&mut self, region_number: u8) -> Result<Option<MpuRegion>, MpuError> {
+        if region_number >= self.region_numbers() {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        let (rbar, rasr) = crate::interrupt::free(|_| unsafe {
+            self.rnr.write(region_number.into());
+            (self.rbar.read(), self.rasr.read())
+        });
+
+        MpuRegion::decode(rbar, rasr)
+    }
+
+    /// Disable an MPU region.
+    /// The region number must be valid.
+    /// This function is executed with interrupts disabled to prevent having inconsistent results.
+    #[inline]
+    pub fn disable_region(&mut self, region_number: u8) -> Result<(), MpuError> {
+        if region_number >= self.region_numbers() {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        crate::interrupt::free(|_| unsafe {
+            self.rnr.write(region_number.into());
+            self.rasr.modify(|rasr| rasr & !MPU_RASR_ENABLE);
+        });
+
+        crate::asm::dsb();
+        crate::asm::isb();
+
+        Ok(())
+    }
+}
+
+/// Maximum number of regions in a [`MpuRegionPlan`], the largest number of regions implemented
+/// by an ARMv7-M MPU.
+#[cfg(not(armv8m))]
+pub const MPU_PLAN_MAX_REGIONS: usize = 16;
+
+/// How [`plan_regions`] is allowed to deviate from the requested range.
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MpuPlanMode {
+    /// Cover exactly the requested range, which must be 32-byte aligned.
+    Exact,
+    /// Cover at least the requested range. The covered range is widened until it fits in the
+    /// region budget, which is always possible with at least one region.
+    Outward,
+    /// Cover at most the requested range. The covered range is shrunk until it fits in the region
+    /// budget, down to an empty plan if needed.
+    Inward,
+}
+
+/// Possible error values returned by [`plan_regions`].
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MpuPlanError {
+    /// The start and length of an exact plan must be multiples of 32 bytes.
+    Unaligned,
+    /// The range goes past the end of the address space.
+    AddressOverflow,
+    /// The range can not be covered with the given number of regions.
+    RegionBudgetExceeded,
+}
+
+/// Base, size and subregion mask of one region computed by [`plan_regions`].
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MpuRegionLayout {
+    /// First address of the region, aligned to the size of the region.
+    pub base_address: u32,
+    /// Size of the region as a power of two.
+    pub size_log2: u8,
+    /// Subregion Disable mask of the region.
+    pub subregion_disable: u8,
+}
+
+#[cfg(not(armv8m))]
+impl MpuRegionLayout {
+    /// Combines this layout with attributes to build a region that can be given to
+    /// [`MPU::set_region`].
+    #[inline]
+    pub fn with_attributes(
+        self,
+        access: MpuAccessPermission,
+        memory_type: MpuMemoryType,
+        execute_never: bool,
+    ) -> MpuRegion {
+        MpuRegion {
+            base_address: self.base_address,
+            size_log2: self.size_log2,
+            access,
+            memory_type,
+            execute_never,
+            subregion_disable: self.subregion_disable,
+        }
+    }
+}
+
+/// Set of regions covering an address range, as computed by [`plan_regions`].
+///
+/// The regions never cover the same address twice, so they can be programmed in any order and
+/// with any region numbers.
+#[cfg(not(armv8m))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MpuRegionPlan {
+    regions: [MpuRegionLayout; MPU_PLAN_MAX_REGIONS],
+    len: usize,
+    budget: usize,
+    start: u64,
+    end: u64,
+}
+
+#[cfg(not(armv8m))]
+impl MpuRegionPlan {
+    #[inline]
+    fn new(budget: usize) -> Self {
+        MpuRegionPlan {
+            regions: [MpuRegionLayout::default(); MPU_PLAN_MAX_REGIONS],
+            len: 0,
+            budget: budget.min(MPU_PLAN_MAX_REGIONS),
+            start: 0,
+            end: 0,
+        }
+    }
+
+    /// Regions of the plan.
+    #[inline]
+    pub fn regions(&self) -> &[MpuRegionLayout] {
+        &self.regions[..self.len]
+    }
+
+    /// First and last address covered by the plan, or `None` if the plan is empty.
+    #[inline]
+    pub fn covered_range(&self) -> Option<core::ops::RangeInclusive<u32>> {
+        if self.start == self.end {
+            None
+        } else {
+            Some(self.start as u32..=(self.end - 1) as u32)
+        }
+    }
+
+    #[inline]
+    fn push(&mut self, region: MpuRegionLayout) -> Result<(), MpuPlanError> {
+        if self.len == self.budget {
+            return Err(MpuPlanError::RegionBudgetExceeded);
+        }
+
+        self.regions[self.len] = region;
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Covers `[lo, hi)`, a run of at most eight `subregion`-sized blocks that all belong to the
+    /// same `8 * subregion`-aligned window.
+    fn push_run(&mut self, lo: u64, hi: u64, subregion: u64) -> Result<(), MpuPlanError> {
+        let len = hi - lo;
+
+        // A single aligned block does not need subregions.
+        if len.is_power_of_two() && lo % len == 0 {
+            return self.push(MpuRegionLayout {
+                base_address: lo as u32,
+                size_log2: len.trailing_zeros() as u8,
+                subregion_disable: 0,
+            });
+        }
+
+        let window = lo & !(8 * subregion - 1);
+        let first = (lo - window) / subregion;
+        let last = (hi - window) / subregion;
+        let enabled = ((1u32 << last) - (1u32 << first)) as u8;
+
+        self.push(MpuRegionLayout {
+            base_address: window as u32,
+            size_log2: (8 * subregion).trailing_zeros() as u8,
+            subregion_disable: !enabled,
+        })
+    }
+
+    /// Covers `[lo, hi)`, where `hi` is aligned to twice the most significant bit of the length,
+    /// by peeling runs of subregions off its end.
+    fn push_suffix(&mut self, lo: u64, mut hi: u64) -> Result<(), MpuPlanError> {
+        while lo < hi {
+            let subregion = Self::run_subregion(hi - lo);
+            let run = (hi - lo) / subregion * subregion;
+            self.push_run(hi - run, hi, subregion)?;
+            hi -= run;
+        }
+
+        Ok(())
+    }
+
+    /// Covers `[lo, hi)`, where `lo` is aligned to twice the most significant bit of the length,
+    /// by peeling runs of subregions off its start.
+    fn push_prefix(&mut self, mut lo: u64, hi: u64) -> Result<(), MpuPlanError> {
+        while lo < hi {
+            let subregion = Self::run_subregion(hi - lo);
+            let run = (hi - lo) / subregion * subregion;
+            self.push_run(lo, lo + run, subregion)?;
+            lo += run;
+        }
+
+        Ok(())
+    }
+
+    /// Subregion size covering the three most significant bits of `len` in a single region.
+    #[inline]
+    fn run_subregion(len: u64) -> u64 {
+        if len.is_power_of_two() {
+            len
+        } else {
+            let msb = 63 - len.leading_zeros();
+            (1 << msb.saturating_sub(2)).max(32)
+        }
+    }
+
+    /// Plans the exact cover of the 32-byte aligned range `[start, end)`.
+    fn exact(start: u64, end: u64, budget: usize) -> Result<Self, MpuPlanError> {
+        let mut plan = MpuRegionPlan::new(budget);
+        plan.start = start;
+        plan.end = end;
+
+        if start == end {
+            return Ok(plan);
+        }
+
+        // `start` and `end - 1` first differ on bit `k`: the range is split by `middle`, which is
+        // aligned to `2^k`, in a suffix and a prefix both shorter than `2^k`.
+        let k = 63 - (start ^ (end - 1)).leading_zeros();
+        let middle = (end - 1) & !((1 << k) - 1);
+
+        let mut split = plan;
+        let split_result = split
+            .push_suffix(start, middle)
+            .and_then(|_| split.push_prefix(middle, end));
+
+        // Alternatively, one region straddling `middle` covers as many of its subregions as
+        // possible, and the leftovers on both sides are covered separately.
+        let mut straddle = plan;
+        let subregion = (1u64 << k.saturating_sub(2)).max(32);
+        let lo = (start + subregion - 1) & !(subregion - 1);
+        let hi = end & !(subregion - 1);
+        let straddle_result = if lo < hi {
+            straddle
+                .push_run(lo, hi, subregion)
+                .and_then(|_| straddle.push_suffix(start, lo))
+                .and_then(|_| straddle.push_prefix(hi, end))
+        } else {
+            Err(MpuPlanError::RegionBudgetExceeded)
+        };
+
+        match (split_result, straddle_result) {
+            (Ok(()), Ok(())) if straddle.len < split.len => Ok(straddle),
+            (Ok(()), _) => Ok(split),
+            (Err(_), Ok(())) => Ok(straddle),
+            (Err(e), Err(_)) => Err(e),
+        }
+    }
+}
+
+/// Computes the MPU regions covering the range of `len` bytes starting at `start`, using at most
+/// `max_regions` regions.
+///
+/// ARMv7-M regions are power-of-two sized and aligned to their size, so an arbitrary range usually
+/// needs several regions and disabled subregions. The planner compares splitting the range on
+/// its most significant aligned boundary with straddling that boundary with a single region, and
+/// returns whichever layout uses fewer regions. The regions cover disjoint parts of the range.
+///
+/// In `Outward` and `Inward` modes, the range is rounded to ever coarser power-of-two boundaries
+/// until its exact plan fits in the budget. `max_regions` is capped to
+/// [`MPU_PLAN_MAX_REGIONS`].
+///
+/// This function does not access the MPU.
+#[cfg(not(armv8m))]
+#[inline]
+pub fn plan_regions(
+    start: u32,
+    len: u32,
+    max_regions: usize,
+    mode: MpuPlanMode,
+) -> Result<MpuRegionPlan, MpuPlanError> {
+    let start = u64::from(start);
+    let end = start + u64::from(len);
+    if end > 1 << 32 {
+        return Err(MpuPlanError::AddressOverflow);
+    }
+
+    if start == end {
+        return MpuRegionPlan::exact(start, end, max_regions);
+    }
+
+    if mode == MpuPlanMode::Exact {
+        if start % 32 != 0 || end % 32 != 0 {
+            return Err(MpuPlanError::Unaligned);
+        }
+        return MpuRegionPlan::exact(start, end, max_regions);
+    }
+
+    for granule_log2 in 5..=32 {
+        let mask = (1u64 << granule_log2) - 1;
+        let (lo, hi) = match mode {
+            MpuPlanMode::Inward => ((start + mask) & !mask, end & !mask),
+            _ => (start & !mask, (end + mask) & !mask),
+        };
+
+        if let Ok(plan) = MpuRegionPlan::exact(lo, hi.max(lo), max_regions) {
+            return Ok(plan);
+        }
+    }
+
+    Err(MpuPlanError::RegionBudgetExceeded)
+}
+
+/// ARMv8-M region and memory attribute encodings
+///
+/// The types are re-exported by this module on ARMv8-M, and only compiled for the host tests
+/// otherwise as their names are shared with the ARMv7-M API.
+#[cfg(any(armv8m, test))]
+#[cfg_attr(not(armv8m), allow(dead_code, clippy::enum_variant_names))]
+pub(crate) mod armv8 {
+    const MPU_RBAR_BASE_MASK: u32 = !0x1F;
+    const MPU_RBAR_SH_SHIFT: u32 = 3;
+    const MPU_RBAR_AP_SHIFT: u32 = 1;
+    const MPU_RBAR_XN: u32 = 1 << 0;
+
+    const MPU_RLAR_LIMIT_MASK: u32 = !0x1F;
+    const MPU_RLAR_PXN: u32 = 1 << 4;
+    const MPU_RLAR_ATTRINDX_SHIFT: u32 = 1;
+    pub(super) const MPU_RLAR_ENABLE: u32 = 1 << 0;
+
+    /// Access permissions of an ARMv8-M MPU region (the `AP` field of `RBAR`).
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum MpuAccessPermission {
+        /// Read/write from privileged code only
+        PrivilegedReadWrite,
+        /// Read/write from any privilege level
+        ReadWrite,
+        /// Read-only from privileged code only
+        PrivilegedReadOnly,
+        /// Read-only from any privilege level
+        ReadOnly,
+    }
+
+    /// Shareability of an ARMv8-M MPU region (the `SH` field of `RBAR`).
+    ///
+    /// This only applies to Normal memory, Device memory is always treated as Outer Shareable.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum MpuShareability {
+        /// Non-shareable
+        NonShareable,
+        /// Outer Shareable
+        OuterShareable,
+        /// Inner Shareable
+        InnerShareable,
+    }
+
+    /// Cache policy of one level (inner or outer) of a Normal memory attribute.
+    ///
+    /// Only the non-transient policies are supported.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum MpuCachePolicy {
+        /// Non-cacheable
+        NonCacheable,
+        /// Write-through
+        WriteThrough {
+            /// Read allocation hint
+            read_allocate: bool,
+            /// Write allocation hint
+            write_allocate: bool,
+        },
+        /// Write-back
+        WriteBack {
+            /// Read allocation hint
+            read_allocate: bool,
+            /// Write allocation hint
+            write_allocate: bool,
+        },
+    }
+
+    /// Memory attribute stored in one of the eight `MAIR` slots and referenced by the
+    /// `attribute_index` of a region.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum MemoryAttribute {
+        /// Device memory, non-Gathering, non-Reordering, no Early write acknowledgement
+        DeviceNGnRnE,
+        /// Device memory, non-Gathering, non-Reordering, Early write acknowledgement
+        DeviceNGnRE,
+        /// Device memory, non-Gathering, Reordering, Early write acknowledgement
+        DeviceNGRE,
+        /// Device memory, Gathering, Reordering, Early write acknowledgement
+        DeviceGRE,
+        /// Normal memory
+        Normal {
+            /// Outer cache policy
+            outer: MpuCachePolicy,
+            /// Inner cache policy
+            inner: MpuCachePolicy,
+        },
+    }
+
+    /// Description of an ARMv8-M MPU region.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub struct MpuRegion {
+        /// First address of the region, its 5 least significant bits must be set to 0.
+        pub base_address: u32,
+        /// Last address of the region, its 5 least significant bits must be set to 1.
+        pub limit_address: u32,
+        /// Shareability of the region.
+        pub shareability: MpuShareability,
+        /// Access permissions of the region.
+        pub access: MpuAccessPermission,
+        /// Whether instruction fetches from the region are forbidden.
+        pub execute_never: bool,
+        /// Whether instruction fetches from the region are forbidden in privileged mode. This field
+        /// is only implemented on Armv8.1-M, it is ignored by Armv8.0-M cores.
+        pub privileged_execute_never: bool,
+        /// Index of the `MAIR` attribute describing the memory type of the region, between 0 and 7.
+        pub attribute_index: u8,
+    }
+
+    /// Possible error values returned by the MPU methods.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    pub enum MpuError {
+        /// The region number parameter to set or get a region must be between 0 and
+        /// region_numbers() - 1.
+        RegionNumberTooBig,
+        /// Bits 0 to 4 of the base address of a region must be set to zero.
+        WrongBaseAddress,
+        /// Bits 0 to 4 of the limit address of a region must be set to one, and the limit address
+        /// must not be lower than the base address.
+        WrongLimitAddress,
+        /// The attribute index must be between 0 and 7.
+        WrongAttributeIndex,
+        /// The region overlaps with another enabled region, which is not allowed on ARMv8-M.
+        RegionOverlap,
+        /// A value read back from the MPU uses a reserved or unsupported encoding.
+        UnsupportedAttributes,
+    }
+
+    impl MpuCachePolicy {
+        #[inline]
+        fn bits(self) -> u8 {
+            let hints = |read_allocate: bool, write_allocate: bool| {
+                (u8::from(read_allocate) << 1) | u8::from(write_allocate)
+            };
+
+            match self {
+                MpuCachePolicy::NonCacheable => 0b0100,
+                MpuCachePolicy::WriteThrough {
+                    read_allocate,
+                    write_allocate,
+                } => 0b1000 | hints(read_allocate, write_allocate),
+                MpuCachePolicy::WriteBack {
+                    read_allocate,
+                    write_allocate,
+                } => 0b1100 | hints(read_allocate, write_allocate),
+            }
+        }
+
+        #[inline]
+        fn from_bits(bits: u8) -> Result<Self, MpuError> {
+            let read_allocate = bits & 0b10 != 0;
+            let write_allocate = bits & 0b01 != 0;
+
+            match bits & 0b1100 {
+                0b1000 => Ok(MpuCachePolicy::WriteThrough {
+                    read_allocate,
+                    write_allocate,
+                }),
+                0b1100 => Ok(MpuCachePolicy::WriteBack {
+                    read_allocate,
+                    write_allocate,
+                }),
+                _ if bits & 0xF == 0b0100 => Ok(MpuCachePolicy::NonCacheable),
+                // Transient policies
+                _ => Err(MpuError::UnsupportedAttributes),
+            }
+        }
+    }
+
+    impl MemoryAttribute {
+        /// Returns the 8-bit `MAIR` encoding of this attribute.
+        #[inline]
+        pub fn bits(self) -> u8 {
+            match self {
+                MemoryAttribute::DeviceNGnRnE => 0b0000_0000,
+                MemoryAttribute::DeviceNGnRE => 0b0000_0100,
+                MemoryAttribute::DeviceNGRE => 0b0000_1000,
+                MemoryAttribute::DeviceGRE => 0b0000_1100,
+                MemoryAttribute::Normal { outer, inner } => (outer.bits() << 4) | inner.bits(),
+            }
+        }
+
+        /// Decodes an 8-bit `MAIR` attribute.
+        #[inline]
+        pub fn from_bits(bits: u8) -> Result<Self, MpuError> {
+            match (bits >> 4, bits & 0xF) {
+                (0, 0b0000) => Ok(MemoryAttribute::DeviceNGnRnE),
+                (0, 0b0100) => Ok(MemoryAttribute::DeviceNGnRE),
+                (0, 0b1000) => Ok(MemoryAttribute::DeviceNGRE),
+                (0, 0b1100) => Ok(MemoryAttribute::DeviceGRE),
+                (0, _) => Err(MpuError::UnsupportedAttributes),
+                (outer, inner) => Ok(MemoryAttribute::Normal {
+                    outer: MpuCachePolicy::from_bits(outer)?,
+                    inner: MpuCachePolicy::from_bits(inner)?,
+                }),
+            }
+        }
+    }
+
+    impl MpuRegion {
+        /// Returns the `RBAR` and `RLAR` values describing this region.
+        pub(crate) fn encode(&self) -> Result<(u32, u32), MpuError> {
+            if self.base_address & !MPU_RBAR_BASE_MASK != 0 {
+                return Err(MpuError::WrongBaseAddress);
+            }
+
+            if self.limit_address & !MPU_RLAR_LIMIT_MASK != !MPU_RLAR_LIMIT_MASK
+                || self.limit_address < self.base_address
+            {
+                return Err(MpuError::WrongLimitAddress);
+            }
+
+            if self.attribute_index > 7 {
+                return Err(MpuError::WrongAttributeIndex);
+            }
+
+            let sh = match self.shareability {
+                MpuShareability::NonShareable => 0b00,
+                MpuShareability::OuterShareable => 0b10,
+                MpuShareability::InnerShareable => 0b11,
+            };
+
+            let ap = match self.access {
+                MpuAccessPermission::PrivilegedReadWrite => 0b00,
+                MpuAccessPermission::ReadWrite => 0b01,
+                MpuAccessPermission::PrivilegedReadOnly => 0b10,
+                MpuAccessPermission::ReadOnly => 0b11,
+            };
+
+            let mut rbar =
+                self.base_address | (sh << MPU_RBAR_SH_SHIFT) | (ap << MPU_RBAR_AP_SHIFT);
+            if self.execute_never {
+                rbar |= MPU_RBAR_XN;
+            }
+
+            let mut rlar = (self.limit_address & MPU_RLAR_LIMIT_MASK)
+                | (u32::from(self.attribute_index) << MPU_RLAR_ATTRINDX_SHIFT)
+                | MPU_RLAR_ENABLE;
+            if self.privileged_execute_never {
+                rlar |= MPU_RLAR_PXN;
+            }
+
+            Ok((rbar, rlar))
+        }
+
+        /// Returns `true` if this region overlaps with the region encoded as `rbar` and `rlar`,
+        /// when that region is enabled.
+        pub(crate) fn overlaps(&self, rbar: u32, rlar: u32) -> bool {
+            rlar & MPU_RLAR_ENABLE != 0
+                && rbar & MPU_RBAR_BASE_MASK <= self.limit_address
+                && self.base_address <= rlar | !MPU_RLAR_LIMIT_MASK
+        }
+
+        /// Decodes the `RBAR` and `RLAR` values of a region.
+        ///
+        /// Returns `Ok(None)` if the region is disabled.
+        pub(crate) fn decode(rbar: u32, rlar: u32) -> Result<Option<Self>, MpuError> {
+            if rlar & MPU_RLAR_ENABLE == 0 {
+                return Ok(None);
+            }
+
+            let shareability = match (rbar >> MPU_RBAR_SH_SHIFT) & 0b11 {
+                0b00 => MpuShareability::NonShareable,
+                0b10 => MpuShareability::OuterShareable,
+                0b11 => MpuShareability::InnerShareable,
+                _ => return Err(MpuError::UnsupportedAttributes),
+            };
+
+            let access = match (rbar >> MPU_RBAR_AP_SHIFT) & 0b11 {
+                0b00 => MpuAccessPermission::PrivilegedReadWrite,
+                0b01 => MpuAccessPermission::ReadWrite,
+                0b10 => MpuAccessPermission::PrivilegedReadOnly,
+                _ => MpuAccessPermission::ReadOnly,
+            };
+
+            Ok(Some(MpuRegion {
+                base_address: rbar & MPU_RBAR_BASE_MASK,
+                limit_address: rlar | !MPU_RLAR_LIMIT_MASK,
+                shareability,
+                access,
+                execute_never: rbar & MPU_RBAR_XN != 0,
+                privileged_execute_never: rlar & MPU_RLAR_PXN != 0,
+                attribute_index: ((rlar >> MPU_RLAR_ATTRINDX_SHIFT) & 0b111) as u8,
+            }))
+        }
+    }
+}
+
+#[cfg(armv8m)]
+impl MPU {
+    /// Set one of the eight `MAIR` memory attributes.
+    /// The attribute index must be between 0 and 7.
+    /// This function is executed with interrupts disabled to prevent having inconsistent results.
+    #[inline]
+    pub fn set_memory_attribute(
+        &mut self,
+        attribute_index: u8,
+        attribute: MemoryAttribute,
+    ) -> Result<(), MpuError> {
+        if attribute_index > 7 {
+            return Err(MpuError::WrongAttributeIndex);
+        }
+
+        let shift = u32::from(attribute_index % 4) * 8;
+        let mair = &self.mair[usize::from(attribute_index / 4)];
+
+        crate::interrupt::free(|_| unsafe {
+            mair.modify(|w| (w & !(0xFF << shift)) | (u32::from(attribute.bits()) << shift));
+        });
+
+        crate::asm::dsb();
+        crate::asm::isb();
+
+        Ok(())
+    }
+
+    /// Get one of the eight `MAIR` memory attributes.
+    /// The attribute index must be between 0 and 7.
+    #[inline]
+    pub fn get_memory_attribute(&self, attribute_index: u8) -> Result<MemoryAttribute, MpuError> {
+        if attribute_index > 7 {
+            return Err(MpuError::WrongAttributeIndex);
+        }
+
+        let shift = u32::from(attribute_index % 4) * 8;
+        let mair = self.mair[usize::from(attribute_index / 4)].read();
+
+        MemoryAttribute::from_bits((mair >> shift) as u8)
+    }
+
+    /// Set an MPU region to a region number and enable it.
+    /// The base address must be 32-byte aligned, the limit address is the last address of the
+    /// region so its 5 least significant bits must be set.
+    /// The region must not overlap with any other enabled region.
+    /// The region number must be valid.
+    /// This function is executed with interrupts disabled to prevent having inconsistent results.
+    #[inline]
+    pub fn set_region(&mut self, region_number: u8, region: MpuRegion) -> Result<(), MpuError> {
+        let region_numbers = self.region_numbers();
+        if region_number >= region_numbers {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        let (rbar, rlar) = region.encode()?;
+
+        crate::interrupt::free(|_| unsafe {
+            for other_number in (0..region_numbers).filter(|&n| n != region_number) {
+                self.rnr.write(other_number.into());
+                if region.overlaps(self.rbar.read(), self.rlar.read()) {
+                    return Err(MpuError::RegionOverlap);
+                }
+            }
+
+            self.rnr.write(region_number.into());
+            self.rbar.write(rbar);
+            self.rlar.write(rlar);
+
+            Ok(())
+        })?;
+
+        crate::asm::dsb();
+        crate::asm::isb();
+
+        Ok(())
+    }
+
+    /// Get a region from the MPU.
+    /// Returns `Ok(None)` if the region is disabled.
+    /// The region number must be valid.
+    /// This function is executed with interrupts disabled to prevent having inconsistent results.
+    #[inline]
+    pub fn get_region(&mut self, region_number: u8) -> Result<Option<MpuRegion>, MpuError> {
+        if region_number >= self.region_numbers() {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        let (rbar, rlar) = crate::interrupt::free(|_| unsafe {
+            self.rnr.write(region_number.into());
+            (self.rbar.read(), self.rlar.read())
+        });
+
+        MpuRegion::decode(rbar, rlar)
+    }
+
+    /// Disable an MPU region.
+    /// The region number must be valid.
+    /// This function is executed with interrupts disabled to prevent having inconsistent results.
+    #[inline]
+    pub fn disable_region(&mut self, region_number: u8) -> Result<(), MpuError> {
+        if region_number >= self.region_numbers() {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        crate::interrupt::free(|_| unsafe {
+            self.rnr.write(region_number.into());
+            self.rlar.modify(|rlar| rlar & !MPU_RLAR_ENABLE);
+        });
+
+        crate::asm::dsb();
+        crate::asm::isb();
+
+        Ok(())
+    }
+}
+
+/// Precomputed encodings of `N` consecutive MPU regions, starting at region `first_region`.
+///
+/// A context is built once, for example when a task is created, and loaded with
+/// [`MPU::load_context`] on every context switch.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MpuContext<const N: usize> {
+    first_region: u8,
+    regions: [(u32, u32); N],
+}
+
+impl<const N: usize> MpuContext<N> {
+    /// Creates a context where all the regions are disabled.
+    #[inline]
+    pub const fn new(first_region: u8) -> Self {
+        #[allow(unused_mut)]
+        let mut regions = [(0, 0); N];
+
+        // The region number is part of the `RBAR` value on ARMv7-M.
+        #[cfg(not(armv8m))]
+        {
+            let mut index = 0;
+            while index < N {
+                regions[index].0 = MPU_RBAR_VALID | (first_region as u32 + index as u32);
+                index += 1;
+            }
+        }
+
+        MpuContext {
+            first_region,
+            regions,
+        }
+    }
+
+    /// Number of the first region of the context.
+    #[inline]
+    pub fn first_region(&self) -> u8 {
+        self.first_region
+    }
+
+    /// Sets the region at position `index` of the context, that is region number
+    /// `first_region + index`.
+    ///
+    /// On ARMv8-M, the region must not overlap with another enabled region of the context,
+    /// `MpuError::RegionOverlap` is returned otherwise. The regions that are not part of the
+    /// context are not checked.
+    #[inline]
+    pub fn set_region(&mut self, index: usize, region: MpuRegion) -> Result<(), MpuError> {
+        if index >= N {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        #[cfg(not(armv8m))]
+        let (rbar, attributes) = {
+            let (rbar, rasr) = region.encode()?;
+            (rbar | (self.regions[index].0 & !MPU_RBAR_ADDR_MASK), rasr)
+        };
+        #[cfg(armv8m)]
+        let (rbar, attributes) = {
+            let others = self.regions.iter().enumerate().filter(|&(i, _)| i != index);
+            for (_, &(rbar, rlar)) in others {
+                if region.overlaps(rbar, rlar) {
+                    return Err(MpuError::RegionOverlap);
+                }
+            }
+
+            region.encode()?
+        };
+
+        self.regions[index] = (rbar, attributes);
+
+        Ok(())
+    }
+
+    /// Disables the region at position `index` of the context.
+    #[inline]
+    pub fn disable_region(&mut self, index: usize) -> Result<(), MpuError> {
+        if index >= N {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        #[cfg(not(armv8m))]
+        {
+            self.regions[index] = (self.regions[index].0 & !MPU_RBAR_ADDR_MASK, 0);
+        }
+        #[cfg(armv8m)]
+        {
+            self.regions[index] = (0, 0);
+        }
+
+        Ok(())
+    }
+}
+
+impl MPU {
+    /// Loads all the regions of a context.
+    ///
+    /// On ARMv7-M, each region takes two stores: the `RBAR` value selects the region through its
+    /// `VALID` and `REGION` fields, and the alias registers are used so that groups of four regions
+    /// are written to consecutive addresses. ARMv6-M does not implement the aliases and uses
+    /// `RBAR`/`RASR` for every region.
+    ///
+    /// On ARMv8-M Mainline, `RNR` is written once per group of four regions and the regions of the
+    /// group are written through the `RBAR`/`RLAR` aliases. ARMv8-M Baseline does not implement
+    /// the aliases and writes `RNR`, `RBAR` and `RLAR` for every region.
+    ///
+    /// The regions of the context must exist on this MPU. On ARMv8-M, the regions of the context
+    /// do not overlap as this is checked by [`MpuContext::set_region`], but they are not checked
+    /// against the regions outside of the context.
+    /// This function is executed with interrupts disabled to prevent having inconsistent results.
+    #[inline]
+    pub fn load_context<const N: usize>(
+        &mut self,
+        context: &MpuContext<N>,
+    ) -> Result<(), MpuError> {
+        if usize::from(context.first_region) + N > usize::from(self.region_numbers()) {
+            return Err(MpuError::RegionNumberTooBig);
+        }
+
+        // Memory accesses made with the old configuration must complete first.
+        crate::asm::dmb();
+
+        crate::interrupt::free(|_| unsafe {
+            #[cfg(armv6m)]
+            for &(rbar, rasr) in context.regions.iter() {
+                self.rbar.write(rbar);
+                self.rasr.write(rasr);
+            }
+
+            #[cfg(not(any(armv6m, armv8m)))]
+            {
+                let aliases = [
+                    (&self.rbar, &self.rasr),
+                    (&self.rbar_a1, &self.rasr_a1),
+                    (&self.rbar_a2, &self.rasr_a2),
+                    (&self.rbar_a3, &self.rasr_a3),
+                ];
+                for regions in context.regions.chunks(4) {
+                    for (&(rbar, rasr), (rbar_alias, rasr_alias)) in regions.iter().zip(aliases) {
+                        rbar_alias.write(rbar);
+                        rasr_alias.write(rasr);
+                    }
+                }
+            }
+
+            // ARMv8-M Baseline has no alias registers, each region is selected through `RNR`.
+            #[cfg(armv8m_base)]
+            for (index, &(rbar, rlar)) in context.regions.iter().enumerate() {
+                self.rnr
+                    .write(u32::from(context.first_region) + index as u32);
+                self.rbar.write(rbar);
+                self.rlar.write(rlar);
+            }
+
+            #[cfg(armv8m_main)]
+            {
+                let aliases = [
+                    (&self.rbar, &self.rlar),
+                    (&self.rbar_a1, &self.rlar_a1),
+                    (&self.rbar_a2, &self.rlar_a2),
+                    (&self.rbar_a3, &self.rlar_a3),
+                ];
+                for (index, &(rbar, rlar)) in context.regions.iter().enumerate() {
+                    let region_number = usize::from(context.first_region) + index;
+                    if index == 0 || region_number % 4 == 0 {
+                        self.rnr.write(region_number as u32);
+                    }
+
+                    let (rbar_alias, rlar_alias) = aliases[region_number % 4];
+                    rbar_alias.write(rbar);
+                    rlar_alias.write(rlar);
+                }
+            }
+        });
+
+        crate::asm::dsb();
+        crate::asm::isb();
+
+        Ok(())
+    }
+}
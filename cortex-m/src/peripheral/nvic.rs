@@ -1,10 +1,16 @@
 //! Nested Vector Interrupt Controller
 
+use core::marker::PhantomData;
+
 use crate::vtypes::RW;
 #[cfg(not(armv6m))]
 use crate::vtypes::{RO, WO};
 
+#[cfg(armv8m)]
+use crate::exception::SecurityState;
 use crate::interrupt::InterruptNumber;
+#[cfg(any(armv7m, armv8m, native))]
+use crate::peripheral::ICB;
 use crate::peripheral::NVIC;
 
 /// Register block
@@ -270,3 +276,593 @@ impl NVIC {
         (usize::from(interrupt.number()) % 4) * 8
     }
 }
+
+/// Logical priority of an exception or interrupt, split in preemption priority and subpriority.
+///
+/// As with hardware priorities, lower values are more urgent. Only the preemption priority
+/// decides whether an exception can preempt another one, the subpriority only orders pending
+/// exceptions of the same preemption priority.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct LogicalPriority {
+    /// Preemption (group) priority
+    pub preempt: u8,
+    /// Subpriority
+    pub sub: u8,
+}
+
+/// Possible error values returned when encoding a [`LogicalPriority`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PriorityError {
+    /// The preemption priority does not fit in the implemented preemption priority bits.
+    PreemptPriorityTooBig,
+    /// The subpriority does not fit in the implemented subpriority bits.
+    SubpriorityTooBig,
+}
+
+/// Layout of the hardware priority byte: the number of priority bits implemented by the core and
+/// the priority grouping (`AIRCR.PRIGROUP`) splitting them between preemption priority and
+/// subpriority.
+///
+/// The implemented bits are the most significant bits of the priority byte. The preemption
+/// priority is made of bits `7` to `PRIGROUP + 1`, the subpriority of bits `PRIGROUP` to `0`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PriorityScheme {
+    implemented_bits: u8,
+    priority_grouping: u8,
+}
+
+impl PriorityScheme {
+    /// Creates a priority scheme from a number of implemented priority bits, between 1 and 8,
+    /// and a `PRIGROUP` value, between 0 and 7.
+    ///
+    /// Returns `None` if one of the values is out of range.
+    #[inline]
+    pub const fn new(implemented_bits: u8, priority_grouping: u8) -> Option<Self> {
+        if implemented_bits == 0 || implemented_bits > 8 || priority_grouping > 7 {
+            None
+        } else {
+            Some(PriorityScheme {
+                implemented_bits,
+                priority_grouping,
+            })
+        }
+    }
+
+    /// Number of priority bits implemented by the core.
+    #[inline]
+    pub fn implemented_bits(&self) -> u8 {
+        self.implemented_bits
+    }
+
+    /// `PRIGROUP` value of the scheme.
+    #[inline]
+    pub fn priority_grouping(&self) -> u8 {
+        self.priority_grouping
+    }
+
+    /// Number of implemented preemption priority bits.
+    #[inline]
+    pub fn preempt_bits(&self) -> u8 {
+        self.implemented_bits.min(7 - self.priority_grouping)
+    }
+
+    /// Number of implemented subpriority bits.
+    #[inline]
+    pub fn sub_bits(&self) -> u8 {
+        self.implemented_bits - self.preempt_bits()
+    }
+
+    /// Encodes a logical priority into a hardware priority byte.
+    #[inline]
+    pub fn encode(&self, priority: LogicalPriority) -> Result<u8, PriorityError> {
+        if u32::from(priority.preempt) >> self.preempt_bits() != 0 {
+            return Err(PriorityError::PreemptPriorityTooBig);
+        }
+        if u32::from(priority.sub) >> self.sub_bits() != 0 {
+            return Err(PriorityError::SubpriorityTooBig);
+        }
+
+        let preempt = u32::from(priority.preempt) << (8 - self.preempt_bits());
+        let sub = u32::from(priority.sub) << (8 - self.implemented_bits);
+
+        Ok((preempt | sub) as u8)
+    }
+
+    /// Decodes a hardware priority byte into a logical priority. Unimplemented bits are ignored.
+    #[inline]
+    pub fn decode(&self, priority: u8) -> LogicalPriority {
+        let priority = u32::from(priority);
+        let sub_mask = (1 << self.sub_bits()) - 1;
+
+        LogicalPriority {
+            preempt: (priority >> (8 - self.preempt_bits())) as u8,
+            sub: ((priority >> (8 - self.implemented_bits)) & sub_mask) as u8,
+        }
+    }
+}
+
+impl NVIC {
+    /// Returns the number of priority bits implemented by the core, between 2 and 8.
+    ///
+    /// The number is detected by writing `0xFF` to the priority of interrupt 0 and reading it
+    /// back: unimplemented bits read as zero. The previous priority is restored, the probe runs
+    /// with interrupts disabled.
+    #[inline]
+    pub fn implemented_priority_bits(&mut self) -> u8 {
+        crate::interrupt::free(|_| unsafe {
+            #[cfg(not(armv6m))]
+            let implemented = {
+                let saved = self.ipr[0].read();
+                self.ipr[0].write(0xFF);
+                let implemented = self.ipr[0].read();
+                self.ipr[0].write(saved);
+                implemented
+            };
+
+            #[cfg(armv6m)]
+            let implemented = {
+                let saved = self.ipr[0].read();
+                self.ipr[0].write(saved | 0xFF);
+                let implemented = self.ipr[0].read() as u8;
+                self.ipr[0].write(saved);
+                implemented
+            };
+
+            implemented.count_ones() as u8
+        })
+    }
+
+    /// Returns the priority scheme of the core, made of the implemented priority bits and the
+    /// current priority grouping.
+    ///
+    /// ARMv6-M and ARMv8-M Baseline do not implement priority grouping: all the implemented bits
+    /// are preemption priority bits.
+    #[inline]
+    pub fn priority_scheme(&mut self) -> PriorityScheme {
+        #[cfg(not(any(armv6m, armv8m_base)))]
+        let priority_grouping = crate::peripheral::SCB::priority_grouping();
+        #[cfg(any(armv6m, armv8m_base))]
+        let priority_grouping = 0;
+
+        PriorityScheme {
+            implemented_bits: self.implemented_priority_bits().max(1),
+            priority_grouping,
+        }
+    }
+
+    /// Returns the logical priority of `interrupt` according to `scheme`.
+    #[inline]
+    pub fn get_logical_priority<I>(interrupt: I, scheme: &PriorityScheme) -> LogicalPriority
+    where
+        I: InterruptNumber,
+    {
+        scheme.decode(Self::get_priority(interrupt))
+    }
+
+    /// Sets the logical priority of `interrupt` according to `scheme`.
+    ///
+    /// # Unsafety
+    ///
+    /// Changing priority levels can break priority-based critical sections (see
+    /// [`register::basepri`](crate::register::basepri)) and compromise memory safety.
+    #[inline]
+    pub unsafe fn set_logical_priority<I>(
+        &mut self,
+        interrupt: I,
+        scheme: &PriorityScheme,
+        priority: LogicalPriority,
+    ) -> Result<(), PriorityError>
+    where
+        I: InterruptNumber,
+    {
+        let priority = scheme.encode(priority)?;
+        self.set_priority(interrupt, priority);
+        Ok(())
+    }
+}
+
+/// Interrupt state scanned by [`Interrupts`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum Scan {
+    Enabled,
+    Pending,
+    #[cfg(not(armv6m))]
+    Active,
+}
+
+/// Iterator over the interrupts that are enabled, pending or active, in increasing number order.
+///
+/// The registers are read one word at a time, as the iteration progresses. The interrupt numbers
+/// are converted to `I`, the numbers that can not be converted are skipped; with `u16` all the
+/// implemented interrupts are yielded.
+///
+/// Created by [`NVIC::enabled_interrupts`], [`NVIC::pending_interrupts`] and
+/// [`NVIC::active_interrupts`].
+#[derive(Clone, Debug)]
+pub struct Interrupts<I = u16> {
+    scan: Scan,
+    /// Index of the next word to read
+    word: usize,
+    /// Number of words to read
+    words: usize,
+    /// Remaining set bits of the word before `word`
+    bits: u32,
+    _interrupt: PhantomData<fn() -> I>,
+}
+
+impl<I> Interrupts<I> {
+    #[inline]
+    fn new(scan: Scan) -> Self {
+        Self::with_words(scan, (NVIC::interrupt_lines() / 32).min(16))
+    }
+
+    /// Creates an iterator over the first `words` registers of `scan`
+    #[inline]
+    pub(crate) fn with_words(scan: Scan, words: usize) -> Self {
+        Interrupts {
+            scan,
+            word: 0,
+            words,
+            bits: 0,
+            _interrupt: PhantomData,
+        }
+    }
+
+    /// Returns the number of the next set bit, reading the registers with `read`
+    #[inline]
+    pub(crate) fn next_number(&mut self, mut read: impl FnMut(usize) -> u32) -> Option<u16> {
+        while self.bits == 0 {
+            if self.word == self.words {
+                return None;
+            }
+
+            self.bits = read(self.word);
+            self.word += 1;
+        }
+
+        let bit = self.bits.trailing_zeros();
+        self.bits &= self.bits - 1;
+
+        Some((self.word - 1) as u16 * 32 + bit as u16)
+    }
+
+    #[inline]
+    fn read(scan: Scan, word: usize) -> u32 {
+        // NOTE(unsafe) atomic read with no side effects
+        unsafe {
+            match scan {
+                Scan::Enabled => (*NVIC::PTR).iser[word].read(),
+                Scan::Pending => (*NVIC::PTR).ispr[word].read(),
+                #[cfg(not(armv6m))]
+                Scan::Active => (*NVIC::PTR).iabr[word].read(),
+            }
+        }
+    }
+}
+
+impl<I> Iterator for Interrupts<I>
+where
+    I: TryFrom<u16>,
+{
+    type Item = I;
+
+    #[inline]
+    fn next(&mut self) -> Option<I> {
+        let scan = self.scan;
+        loop {
+            let nr = self.next_number(|word| Self::read(scan, word))?;
+            if let Ok(interrupt) = I::try_from(nr) {
+                return Some(interrupt);
+            }
+        }
+    }
+}
+
+impl NVIC {
+    /// Returns the number of interrupt lines supported by the NVIC, a multiple of 32.
+    ///
+    /// It is read from [`ICB::ictr`](crate::peripheral::icb::RegisterBlock::ictr); ARMv6-M does
+    /// not implement that register, 32 lines are assumed.
+    ///
+    /// Not all the lines are necessarily connected to an interrupt of the device.
+    #[inline]
+    pub fn interrupt_lines() -> usize {
+        #[cfg(any(armv7m, armv8m, native))]
+        // NOTE(unsafe) atomic read with no side effects
+        let lines = 32 * (((unsafe { (*ICB::PTR).ictr.read() } & 0xF) as usize) + 1);
+        #[cfg(not(any(armv7m, armv8m, native)))]
+        let lines = 32;
+
+        lines
+    }
+
+    /// Returns an iterator over the enabled interrupts.
+    ///
+    /// `I` is `u16` for raw interrupt numbers, or a device interrupt enum that can be converted
+    /// from `u16`.
+    #[inline]
+    pub fn enabled_interrupts<I>() -> Interrupts<I>
+    where
+        I: TryFrom<u16>,
+    {
+        Interrupts::new(Scan::Enabled)
+    }
+
+    /// Returns an iterator over the pending interrupts.
+    ///
+    /// `I` is `u16` for raw interrupt numbers, or a device interrupt enum that can be converted
+    /// from `u16`.
+    #[inline]
+    pub fn pending_interrupts<I>() -> Interrupts<I>
+    where
+        I: TryFrom<u16>,
+    {
+        Interrupts::new(Scan::Pending)
+    }
+
+    /// Returns an iterator over the active interrupts, running or preempted and stacked.
+    ///
+    /// `I` is `u16` for raw interrupt numbers, or a device interrupt enum that can be converted
+    /// from `u16`.
+    #[cfg(not(armv6m))]
+    #[inline]
+    pub fn active_interrupts<I>() -> Interrupts<I>
+    where
+        I: TryFrom<u16>,
+    {
+        Interrupts::new(Scan::Active)
+    }
+}
+
+/// Set of device interrupts, one bit per interrupt number.
+///
+/// Used to mask a subset of the interrupts with [`NVIC::mask_set`] and to enable them again
+/// with [`NVIC::restore`].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct NvicMask {
+    words: [u32; 16],
+}
+
+impl NvicMask {
+    /// Creates an empty set
+    #[inline]
+    pub const fn new() -> Self {
+        NvicMask { words: [0; 16] }
+    }
+
+    /// Creates a set from the words of the NVIC registers: bit `n` of word `w` is interrupt
+    /// `32 * w + n`
+    #[inline]
+    pub const fn from_words(words: [u32; 16]) -> Self {
+        NvicMask { words }
+    }
+
+    /// Returns the words of the set, in the layout of the NVIC registers
+    #[inline]
+    pub const fn words(&self) -> [u32; 16] {
+        self.words
+    }
+
+    /// Adds `interrupt` to the set
+    #[inline]
+    pub fn insert<I>(&mut self, interrupt: I)
+    where
+        I: InterruptNumber,
+    {
+        let nr = interrupt.number();
+        self.words[usize::from(nr / 32)] |= 1 << (nr % 32);
+    }
+
+    /// Removes `interrupt` from the set
+    #[inline]
+    pub fn remove<I>(&mut self, interrupt: I)
+    where
+        I: InterruptNumber,
+    {
+        let nr = interrupt.number();
+        self.words[usize::from(nr / 32)] &= !(1 << (nr % 32));
+    }
+
+    /// Checks if `interrupt` is in the set
+    #[inline]
+    pub fn contains<I>(&self, interrupt: I) -> bool
+    where
+        I: InterruptNumber,
+    {
+        let nr = interrupt.number();
+        self.words[usize::from(nr / 32)] & (1 << (nr % 32)) != 0
+    }
+
+    /// Checks if the set is empty
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    /// Returns the interrupts that are in both `self` and `other`
+    #[inline]
+    pub fn intersection(&self, other: &NvicMask) -> NvicMask {
+        let mut words = self.words;
+        for (word, other) in words.iter_mut().zip(other.words.iter()) {
+            *word &= other;
+        }
+        NvicMask { words }
+    }
+}
+
+impl<I> FromIterator<I> for NvicMask
+where
+    I: InterruptNumber,
+{
+    #[inline]
+    fn from_iter<T: IntoIterator<Item = I>>(interrupts: T) -> Self {
+        let mut mask = NvicMask::new();
+        for interrupt in interrupts {
+            mask.insert(interrupt);
+        }
+        mask
+    }
+}
+
+impl NVIC {
+    /// Returns the set of the enabled interrupts
+    #[inline]
+    pub fn snapshot_enabled() -> NvicMask {
+        let mut mask = NvicMask::new();
+        let words = (Self::interrupt_lines() / 32).min(16);
+        for (w, word) in mask.words[..words].iter_mut().enumerate() {
+            // NOTE(unsafe) atomic read with no side effects
+            *word = unsafe { (*Self::PTR).iser[w].read() };
+        }
+        mask
+    }
+
+    /// Disables the interrupts of `mask`, the other interrupts are left unchanged
+    ///
+    /// Returns the interrupts of `mask` that were enabled, to be passed to [`NVIC::restore`].
+    /// The interrupts are masked when this function returns.
+    #[inline]
+    pub fn mask_set(mask: &NvicMask) -> NvicMask {
+        let enabled = Self::snapshot_enabled().intersection(mask);
+
+        for (w, &word) in mask.words.iter().enumerate() {
+            if word != 0 {
+                // NOTE(unsafe) atomic stateless write; ICER doesn't store any state
+                unsafe { (*Self::PTR).icer[w].write(word) }
+            }
+        }
+
+        // Make sure the interrupts can not be taken after this point
+        crate::asm::dsb();
+        crate::asm::isb();
+
+        enabled
+    }
+
+    /// Enables the interrupts of `mask`, the other interrupts are left unchanged
+    ///
+    /// # Unsafety
+    ///
+    /// This can break mask-based critical sections, `mask` must only contain interrupts that were
+    /// enabled before the matching [`NVIC::mask_set`].
+    #[inline]
+    pub unsafe fn restore(mask: &NvicMask) {
+        for (w, &word) in mask.words.iter().enumerate() {
+            if word != 0 {
+                (*Self::PTR).iser[w].write(word)
+            }
+        }
+    }
+
+    /// Disables the interrupts of `mask` until the returned guard is dropped
+    ///
+    /// When dropped, the guard enables again the interrupts of `mask` that were enabled.
+    #[inline]
+    pub fn mask_guard(&mut self, mask: &NvicMask) -> NvicMaskGuard<'_> {
+        NvicMaskGuard {
+            _nvic: self,
+            enabled: Self::mask_set(mask),
+        }
+    }
+}
+
+/// Guard returned by [`NVIC::mask_guard`], enables again the masked interrupts when dropped.
+///
+/// The guard borrows the `NVIC` so that the guards of a context are dropped in reverse creation
+/// order.
+pub struct NvicMaskGuard<'a> {
+    _nvic: &'a mut NVIC,
+    enabled: NvicMask,
+}
+
+impl NvicMaskGuard<'_> {
+    /// Returns the masked interrupts that will be enabled again on drop
+    #[inline]
+    pub fn enabled(&self) -> &NvicMask {
+        &self.enabled
+    }
+}
+
+impl Drop for NvicMaskGuard<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        // NOTE(unsafe) only the interrupts enabled before the guard was created are enabled
+        unsafe { NVIC::restore(&self.enabled) }
+    }
+}
+
+#[cfg(armv8m)]
+impl NVIC {
+    /// Returns the security state `interrupt` targets (`ITNS`)
+    ///
+    /// Always reads `Secure` from the Non-secure state.
+    #[inline]
+    pub fn target_state<I>(interrupt: I) -> SecurityState
+    where
+        I: InterruptNumber,
+    {
+        let nr = interrupt.number();
+        let mask = 1 << (nr % 32);
+
+        // NOTE(unsafe) atomic read with no side effects
+        if unsafe { (*Self::PTR).itns[usize::from(nr / 32)].read() } & mask == mask {
+            SecurityState::NonSecure
+        } else {
+            SecurityState::Secure
+        }
+    }
+
+    /// Sets the security state `interrupt` targets (`ITNS`)
+    ///
+    /// The handler of the vector table of that state is executed when the interrupt is taken.
+    /// This has no effect from the Non-secure state.
+    #[inline]
+    pub fn set_target_state<I>(&mut self, interrupt: I, state: SecurityState)
+    where
+        I: InterruptNumber,
+    {
+        let nr = interrupt.number();
+        let mask = 1 << (nr % 32);
+
+        // NOTE(unsafe) the mutable reference to NVIC makes sure that only this code is currently
+        // modifying the register
+        unsafe {
+            self.itns[usize::from(nr / 32)].modify(|itns| match state {
+                SecurityState::Secure => itns & !mask,
+                SecurityState::NonSecure => itns | mask,
+            })
+        }
+    }
+
+    /// Sets the security state targeted by each interrupt of `table`
+    ///
+    /// Each `ITNS` register is written at most once. If an interrupt appears several times, its
+    /// last entry is used.
+    #[inline]
+    pub fn set_target_states<I>(&mut self, table: &[(I, SecurityState)])
+    where
+        I: InterruptNumber,
+    {
+        let mut changed = [0u32; 16];
+        let mut non_secure = [0u32; 16];
+
+        for &(interrupt, state) in table {
+            let nr = interrupt.number();
+            let (w, mask) = (usize::from(nr / 32), 1 << (nr % 32));
+
+            changed[w] |= mask;
+            match state {
+                SecurityState::Secure => non_secure[w] &= !mask,
+                SecurityState::NonSecure => non_secure[w] |= mask,
+            }
+        }
+
+        for (w, (&changed, &non_secure)) in changed.iter().zip(non_secure.iter()).enumerate() {
+            if changed != 0 {
+                // NOTE(unsafe) the mutable reference to NVIC makes sure that only this code is
+                // currently modifying the register
+                unsafe { self.itns[w].modify(|itns| (itns & !changed) | non_secure) }
+            }
+        }
+    }
+}
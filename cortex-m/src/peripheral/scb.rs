@@ -1,15 +1,19 @@
 //! System Control Block
 
+use core::cell::UnsafeCell;
+use core::mem;
 use core::ptr;
 
+use crate::interrupt::InterruptNumber;
 use crate::vtypes::RW;
 
 #[cfg(not(armv6m))]
 use super::cpuid::CsselrCacheType;
+use super::nvic::{LogicalPriority, PriorityError, PriorityScheme};
 #[cfg(not(armv6m))]
 use super::CBP;
-#[cfg(not(armv6m))]
 use super::CPUID;
+use super::NVIC;
 use super::SCB;
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
@@ -260,6 +264,28 @@ impl Exception {
             Exception::SysTick => -1,
         }
     }
+
+    /// Returns the `SystemHandler` of this `Exception`, or `None` for `NonMaskableInt` and
+    /// `HardFault` whose priority is fixed
+    #[inline]
+    pub fn system_handler(self) -> Option<SystemHandler> {
+        match self {
+            Exception::NonMaskableInt | Exception::HardFault => None,
+            #[cfg(not(armv6m))]
+            Exception::MemoryManagement => Some(SystemHandler::MemoryManagement),
+            #[cfg(not(armv6m))]
+            Exception::BusFault => Some(SystemHandler::BusFault),
+            #[cfg(not(armv6m))]
+            Exception::UsageFault => Some(SystemHandler::UsageFault),
+            #[cfg(any(armv8m, native))]
+            Exception::SecureFault => Some(SystemHandler::SecureFault),
+            Exception::SVCall => Some(SystemHandler::SVCall),
+            #[cfg(not(armv6m))]
+            Exception::DebugMonitor => Some(SystemHandler::DebugMonitor),
+            Exception::PendSV => Some(SystemHandler::PendSV),
+            Exception::SysTick => Some(SystemHandler::SysTick),
+        }
+    }
 }
 
 /// Active exception number
@@ -792,6 +818,227 @@ impl SCB {
     }
 }
 
+#[cfg(not(any(armv6m, armv8m)))]
+const SCB_CCR_NONBASETHRDENA: u32 = 1 << 0;
+#[cfg(not(any(armv6m, armv8m_base)))]
+const SCB_CCR_USERSETMPEND: u32 = 1 << 1;
+const SCB_CCR_UNALIGN_TRP: u32 = 1 << 3;
+#[cfg(not(any(armv6m, armv8m_base)))]
+const SCB_CCR_DIV_0_TRP: u32 = 1 << 4;
+#[cfg(not(any(armv6m, armv8m_base)))]
+const SCB_CCR_BFHFNMIGN: u32 = 1 << 8;
+#[cfg(not(any(armv6m, armv8m)))]
+const SCB_CCR_STKALIGN: u32 = 1 << 9;
+#[cfg(armv8m)]
+const SCB_CCR_STKOFHFNMIGN: u32 = 1 << 10;
+#[cfg(armv8m_main)]
+const SCB_CCR_BP: u32 = 1 << 18;
+// DC and IC, only changed by the cache maintenance functions
+const SCB_CCR_CACHE_MASK: u32 = (1 << 16) | (1 << 17);
+
+/// Configuration and Control register
+///
+/// Only the bits implemented by the core are exposed, the cache enable bits are left to the
+/// cache maintenance functions such as [`SCB::enable_icache`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Ccr {
+    bits: u32,
+}
+
+impl Ccr {
+    /// Creates a `Ccr` value from raw bits.
+    #[inline]
+    pub fn from_bits(bits: u32) -> Self {
+        Self { bits }
+    }
+
+    /// Returns the contents of the register as raw bits
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    #[inline]
+    fn bit(self, mask: u32) -> bool {
+        self.bits & mask != 0
+    }
+
+    #[cfg(not(armv6m))]
+    #[inline]
+    fn set_bit(&mut self, mask: u32, value: bool) {
+        if value {
+            self.bits |= mask;
+        } else {
+            self.bits &= !mask;
+        }
+    }
+
+    /// Whether the processor can enter Thread mode with exceptions active (NONBASETHRDENA)
+    #[cfg(not(any(armv6m, armv8m)))]
+    #[inline]
+    pub fn nonbasethrdena(self) -> bool {
+        self.bit(SCB_CCR_NONBASETHRDENA)
+    }
+
+    /// Sets the NONBASETHRDENA value.
+    #[cfg(not(any(armv6m, armv8m)))]
+    #[inline]
+    pub fn set_nonbasethrdena(&mut self, value: bool) {
+        self.set_bit(SCB_CCR_NONBASETHRDENA, value)
+    }
+
+    /// Whether unprivileged code can pend interrupts through `STIR` (USERSETMPEND)
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn usersetmpend(self) -> bool {
+        self.bit(SCB_CCR_USERSETMPEND)
+    }
+
+    /// Sets the USERSETMPEND value.
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn set_usersetmpend(&mut self, value: bool) {
+        self.set_bit(SCB_CCR_USERSETMPEND, value)
+    }
+
+    /// Whether unaligned word and halfword accesses trap (UNALIGN_TRP)
+    ///
+    /// This bit always reads as one on ARMv6-M and ARMv8-M Baseline, which do not support
+    /// unaligned accesses.
+    #[inline]
+    pub fn unalign_trp(self) -> bool {
+        self.bit(SCB_CCR_UNALIGN_TRP)
+    }
+
+    /// Sets the UNALIGN_TRP value.
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn set_unalign_trp(&mut self, value: bool) {
+        self.set_bit(SCB_CCR_UNALIGN_TRP, value)
+    }
+
+    /// Whether integer divisions by zero trap (DIV_0_TRP)
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn div_0_trp(self) -> bool {
+        self.bit(SCB_CCR_DIV_0_TRP)
+    }
+
+    /// Sets the DIV_0_TRP value.
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn set_div_0_trp(&mut self, value: bool) {
+        self.set_bit(SCB_CCR_DIV_0_TRP, value)
+    }
+
+    /// Whether precise data bus faults are ignored by handlers running at priority -1 or -2
+    /// (BFHFNMIGN)
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn bfhfnmign(self) -> bool {
+        self.bit(SCB_CCR_BFHFNMIGN)
+    }
+
+    /// Sets the BFHFNMIGN value.
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn set_bfhfnmign(&mut self, value: bool) {
+        self.set_bit(SCB_CCR_BFHFNMIGN, value)
+    }
+
+    /// Whether the stack is aligned to 8 bytes on exception entry (STKALIGN)
+    ///
+    /// Stacking is always 8-byte aligned on ARMv6-M and ARMv8-M.
+    #[cfg(not(any(armv6m, armv8m)))]
+    #[inline]
+    pub fn stkalign(self) -> bool {
+        self.bit(SCB_CCR_STKALIGN)
+    }
+
+    /// Sets the STKALIGN value.
+    #[cfg(not(any(armv6m, armv8m)))]
+    #[inline]
+    pub fn set_stkalign(&mut self, value: bool) {
+        self.set_bit(SCB_CCR_STKALIGN, value)
+    }
+
+    /// Whether stack limit violations are ignored by handlers running at priority -1 or -2
+    /// (STKOFHFNMIGN)
+    #[cfg(armv8m)]
+    #[inline]
+    pub fn stkofhfnmign(self) -> bool {
+        self.bit(SCB_CCR_STKOFHFNMIGN)
+    }
+
+    /// Sets the STKOFHFNMIGN value.
+    #[cfg(armv8m)]
+    #[inline]
+    pub fn set_stkofhfnmign(&mut self, value: bool) {
+        self.set_bit(SCB_CCR_STKOFHFNMIGN, value)
+    }
+
+    /// Whether branch prediction is enabled (BP)
+    ///
+    /// The bit was added by ARMv8.1-M, it is RES0 on ARMv8.0-M cores: it reads as zero and
+    /// setting it has no effect.
+    #[cfg(armv8m_main)]
+    #[inline]
+    pub fn bp(self) -> bool {
+        self.bit(SCB_CCR_BP)
+    }
+
+    /// Sets the BP value.
+    #[cfg(armv8m_main)]
+    #[inline]
+    pub fn set_bp(&mut self, value: bool) {
+        self.set_bit(SCB_CCR_BP, value)
+    }
+}
+
+impl SCB {
+    /// Reads the Configuration and Control register
+    #[inline]
+    pub fn ccr() -> Ccr {
+        // NOTE(unsafe) atomic read with no side effects
+        Ccr::from_bits(unsafe { (*Self::PTR).ccr.read() })
+    }
+
+    /// Writes the Configuration and Control register
+    ///
+    /// The cache enable bits are not modified. The write is followed by a `DSB` and an `ISB` so
+    /// that the new configuration applies to the following instructions.
+    ///
+    /// # Unsafety
+    ///
+    /// Some of the bits change how exceptions are taken and returned from, for example
+    /// NONBASETHRDENA or STKALIGN. They must not be changed while they can break the code
+    /// currently running, such as from an exception handler.
+    #[inline]
+    pub unsafe fn set_ccr(&mut self, ccr: Ccr) {
+        self.ccr
+            .modify(|r| (r & SCB_CCR_CACHE_MASK) | (ccr.bits() & !SCB_CCR_CACHE_MASK));
+        crate::asm::dsb();
+        crate::asm::isb();
+    }
+
+    /// Modifies the Configuration and Control register
+    ///
+    /// See [`SCB::set_ccr`].
+    ///
+    /// # Unsafety
+    ///
+    /// See [`SCB::set_ccr`].
+    #[inline]
+    pub unsafe fn modify_ccr<F>(&mut self, f: F)
+    where
+        F: FnOnce(&mut Ccr),
+    {
+        let mut ccr = Self::ccr();
+        f(&mut ccr);
+        self.set_ccr(ccr);
+    }
+}
+
 const SCB_SCR_SLEEPDEEP: u32 = 0x1 << 2;
 
 impl SCB {
@@ -832,9 +1079,115 @@ impl SCB {
     }
 }
 
+#[cfg(cortex_m)]
+const SCB_SCR_SEVONPEND: u32 = 0x1 << 4;
+
+/// Low-power mode entered by [`SCB::enter`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum PowerMode {
+    /// Sleep until an interrupt is pending (`WFI`)
+    Sleep,
+
+    /// Deep sleep until an interrupt is pending (SLEEPDEEP and `WFI`)
+    ///
+    /// What deep sleep turns off is device specific.
+    DeepSleep,
+
+    /// Sleep, and go back to sleep every time the handlers return to Thread mode (SLEEPONEXIT and
+    /// `WFI`)
+    ///
+    /// The application then runs from the interrupt handlers, [`SCB::enter`] only returns once one
+    /// of them clears SLEEPONEXIT with [`SCB::clear_sleeponexit`].
+    SleepOnExit,
+
+    /// Sleep until an event is received (SEVONPEND and `WFE`)
+    ///
+    /// Interrupts are masked while waiting, SEVONPEND is set so that an interrupt becoming pending
+    /// still generates a wakeup event. The event register can already be set when entering, so
+    /// this mode can return without sleeping and the wakeup condition must be checked again.
+    WaitForEvent,
+}
+
+#[cfg(cortex_m)]
+impl PowerMode {
+    /// Returns `scr` updated with the SLEEPDEEP, SLEEPONEXIT and SEVONPEND bits of this mode
+    #[inline]
+    fn scr(self, scr: u32) -> u32 {
+        let scr = scr & !(SCB_SCR_SLEEPDEEP | SCB_SCR_SLEEPONEXIT | SCB_SCR_SEVONPEND);
+
+        match self {
+            PowerMode::Sleep => scr,
+            PowerMode::DeepSleep => scr | SCB_SCR_SLEEPDEEP,
+            PowerMode::SleepOnExit => scr | SCB_SCR_SLEEPONEXIT,
+            PowerMode::WaitForEvent => scr | SCB_SCR_SEVONPEND,
+        }
+    }
+}
+
+impl SCB {
+    /// Enters the low-power `mode` if `condition` returns `true`
+    ///
+    /// Interrupts are disabled while `condition` runs and until the processor sleeps: an interrupt
+    /// that makes the condition `false` after it was checked still wakes the processor up, it is
+    /// taken right after this function re-enables interrupts. Interrupts are only re-enabled if
+    /// they were enabled when this function was called.
+    ///
+    /// The SCR register is restored to its previous value before returning.
+    ///
+    /// Returns whether the processor was put to sleep.
+    #[cfg(cortex_m)]
+    #[inline]
+    pub fn enter<F>(&mut self, mode: PowerMode, condition: F) -> bool
+    where
+        F: FnOnce() -> bool,
+    {
+        let primask = crate::register::primask::read();
+        crate::interrupt::disable();
+
+        let scr = self.scr.read();
+        // NOTE(unsafe) only the low-power bits are changed, and they are restored below
+        unsafe { self.scr.write(mode.scr(scr)) };
+
+        let sleep = condition();
+        if sleep {
+            // Complete the outstanding memory accesses before sleeping
+            crate::asm::dsb();
+
+            match mode {
+                PowerMode::WaitForEvent => crate::asm::wfe(),
+                _ => crate::asm::wfi(),
+            }
+        }
+
+        // With SLEEPONEXIT, the handlers must run and clear it before the SCR can be restored.
+        // Otherwise restore it first, so that the handlers run with the previous configuration.
+        let restore_after = sleep && mode == PowerMode::SleepOnExit;
+        if !restore_after {
+            unsafe { self.scr.write(scr) };
+        }
+
+        if primask.is_active() {
+            unsafe { crate::interrupt::enable() };
+        }
+
+        if restore_after {
+            unsafe { self.scr.write(scr) };
+        }
+
+        sleep
+    }
+}
+
 const SCB_AIRCR_VECTKEY: u32 = 0x05FA << 16;
+#[cfg(not(armv6m))]
+const SCB_AIRCR_VECTKEY_MASK: u32 = 0xFFFF << 16;
+#[cfg(not(any(armv6m, armv8m_base)))]
+const SCB_AIRCR_PRIGROUP_SHIFT: u32 = 8;
 const SCB_AIRCR_PRIGROUP_MASK: u32 = 0x7 << 8;
 const SCB_AIRCR_SYSRESETREQ: u32 = 1 << 2;
+// VECTRESET, VECTCLRACTIVE and SYSRESETREQ trigger an action when written with 1
+#[cfg(not(armv6m))]
+const SCB_AIRCR_ACTIONS_MASK: u32 = 0b111;
 
 impl SCB {
     /// Initiate a system reset request to reset the MCU
@@ -858,6 +1211,185 @@ impl SCB {
     }
 }
 
+#[cfg(not(armv6m))]
+impl SCB {
+    /// Modifies AIRCR: the bits of `clear` are cleared and the bits of `set` are set, the other
+    /// configuration bits are kept and the action bits are written with zero.
+    #[inline]
+    unsafe fn modify_aircr(&mut self, clear: u32, set: u32) {
+        self.aircr.modify(|r| {
+            SCB_AIRCR_VECTKEY
+                | (r & !(SCB_AIRCR_VECTKEY_MASK | SCB_AIRCR_ACTIONS_MASK | clear))
+                | set
+        });
+    }
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+impl SCB {
+    /// Returns the priority grouping (`AIRCR.PRIGROUP`), between 0 and 7.
+    ///
+    /// The preemption priority is made of bits `7` to `PRIGROUP + 1` of the priority byte and
+    /// the subpriority of bits `PRIGROUP` to `0`. See
+    /// [`PriorityScheme`].
+    #[inline]
+    pub fn priority_grouping() -> u8 {
+        // NOTE(unsafe) atomic read with no side effects
+        let aircr = unsafe { (*Self::PTR).aircr.read() };
+        ((aircr & SCB_AIRCR_PRIGROUP_MASK) >> SCB_AIRCR_PRIGROUP_SHIFT) as u8
+    }
+
+    /// Sets the priority grouping (`AIRCR.PRIGROUP`). Only the 3 least significant bits of
+    /// `priority_grouping` are used.
+    ///
+    /// # Unsafety
+    ///
+    /// Changing the priority grouping changes which exceptions can preempt each other, it can
+    /// break priority-based critical sections (see [`register::basepri`](crate::register::basepri))
+    /// and compromise memory safety.
+    #[inline]
+    pub unsafe fn set_priority_grouping(&mut self, priority_grouping: u8) {
+        self.modify_aircr(
+            SCB_AIRCR_PRIGROUP_MASK,
+            (u32::from(priority_grouping) << SCB_AIRCR_PRIGROUP_SHIFT) & SCB_AIRCR_PRIGROUP_MASK,
+        );
+    }
+}
+
+#[cfg(armv7m)]
+const SCB_AIRCR_VECTRESET: u32 = 1 << 0;
+#[cfg(armv7m)]
+const SCB_AIRCR_VECTCLRACTIVE: u32 = 1 << 1;
+
+#[cfg(armv7m)]
+impl SCB {
+    /// Resets the processor core only, without resetting the rest of the system (`VECTRESET`)
+    ///
+    /// # Unsafety
+    ///
+    /// The behavior is unpredictable if the processor is not halted in Debug state.
+    #[inline]
+    pub unsafe fn vect_reset(&mut self) -> ! {
+        crate::asm::dsb();
+        self.modify_aircr(0, SCB_AIRCR_VECTRESET);
+        crate::asm::dsb();
+        loop {
+            // wait for the reset
+            crate::asm::nop(); // avoid rust-lang/rust#28728
+        }
+    }
+
+    /// Clears all the active state information of the exceptions, including the IPSR and the
+    /// stacked state (`VECTCLRACTIVE`)
+    ///
+    /// # Unsafety
+    ///
+    /// The behavior is unpredictable if the processor is not halted in Debug state.
+    #[inline]
+    pub unsafe fn vect_clear_active(&mut self) {
+        self.modify_aircr(0, SCB_AIRCR_VECTCLRACTIVE);
+    }
+}
+
+#[cfg(armv8m)]
+const SCB_AIRCR_SYSRESETREQS: u32 = 1 << 3;
+#[cfg(armv8m)]
+const SCB_AIRCR_BFHFNMINS: u32 = 1 << 13;
+#[cfg(armv8m)]
+const SCB_AIRCR_PRIS: u32 = 1 << 14;
+
+/// Error returned when a Secure-only AIRCR field is accessed from the Non-secure state
+#[cfg(armv8m)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AircrError {
+    /// The processor is executing in Non-secure state, or does not implement the Security
+    /// Extension
+    NonSecure,
+}
+
+#[cfg(armv8m)]
+impl SCB {
+    /// Checks that the processor is executing in Secure state.
+    ///
+    /// The Security attribute returned by `TT` is always zero in the Non-secure state and when the
+    /// Security Extension is not implemented; this function is in Secure memory otherwise.
+    #[inline]
+    fn check_secure() -> Result<(), AircrError> {
+        use crate::cmse::{AccessType, TestTarget};
+
+        let address = Self::check_secure as *const () as *mut u32;
+        if TestTarget::check(address, AccessType::Current).secure() {
+            Ok(())
+        } else {
+            Err(AircrError::NonSecure)
+        }
+    }
+
+    #[inline]
+    fn read_aircr_secure(mask: u32) -> Result<bool, AircrError> {
+        Self::check_secure()?;
+        // NOTE(unsafe) atomic read with no side effects
+        Ok(unsafe { (*Self::PTR).aircr.read() } & mask != 0)
+    }
+
+    #[inline]
+    unsafe fn write_aircr_secure(&mut self, mask: u32, value: bool) -> Result<(), AircrError> {
+        Self::check_secure()?;
+        self.modify_aircr(mask, if value { mask } else { 0 });
+        Ok(())
+    }
+
+    /// Returns whether only the Secure state can request a system reset (`SYSRESETREQS`)
+    #[inline]
+    pub fn sysresetreqs() -> Result<bool, AircrError> {
+        Self::read_aircr_secure(SCB_AIRCR_SYSRESETREQS)
+    }
+
+    /// Sets whether only the Secure state can request a system reset (`SYSRESETREQS`)
+    #[inline]
+    pub fn set_sysresetreqs(&mut self, secure_only: bool) -> Result<(), AircrError> {
+        // NOTE(unsafe) restricting the system reset requests does not affect memory safety
+        unsafe { self.write_aircr_secure(SCB_AIRCR_SYSRESETREQS, secure_only) }
+    }
+
+    /// Returns whether BusFault, HardFault and NMI target the Non-secure state (`BFHFNMINS`)
+    #[inline]
+    pub fn bfhfnmins() -> Result<bool, AircrError> {
+        Self::read_aircr_secure(SCB_AIRCR_BFHFNMINS)
+    }
+
+    /// Sets whether BusFault, HardFault and NMI target the Non-secure state (`BFHFNMINS`)
+    ///
+    /// # Unsafety
+    ///
+    /// Non-secure handlers are executed for those exceptions afterwards, even for faults raised by
+    /// Secure code. The Non-secure vector table must be set up before.
+    #[inline]
+    pub unsafe fn set_bfhfnmins(&mut self, non_secure: bool) -> Result<(), AircrError> {
+        self.write_aircr_secure(SCB_AIRCR_BFHFNMINS, non_secure)
+    }
+
+    /// Returns whether the Secure exceptions are prioritized (`PRIS`)
+    ///
+    /// When set, the priorities of the Non-secure exceptions are mapped to the bottom half of the
+    /// priority range, `0x80` to `0xFF`.
+    #[inline]
+    pub fn pris() -> Result<bool, AircrError> {
+        Self::read_aircr_secure(SCB_AIRCR_PRIS)
+    }
+
+    /// Sets whether the Secure exceptions are prioritized (`PRIS`)
+    ///
+    /// # Unsafety
+    ///
+    /// Changing the priority mapping changes which exceptions can preempt each other, it can
+    /// break priority-based critical sections and compromise memory safety.
+    #[inline]
+    pub unsafe fn set_pris(&mut self, prioritize_secure: bool) -> Result<(), AircrError> {
+        self.write_aircr_secure(SCB_AIRCR_PRIS, prioritize_secure)
+    }
+}
+
 const SCB_ICSR_PENDSVSET: u32 = 1 << 28;
 const SCB_ICSR_PENDSVCLR: u32 = 1 << 27;
 
@@ -1027,6 +1559,33 @@ impl SCB {
         }
     }
 
+    /// Returns the logical priority of `system_handler` according to `scheme`.
+    #[inline]
+    pub fn get_logical_priority(
+        system_handler: SystemHandler,
+        scheme: &PriorityScheme,
+    ) -> LogicalPriority {
+        scheme.decode(Self::get_priority(system_handler))
+    }
+
+    /// Sets the logical priority of `system_handler` according to `scheme`.
+    ///
+    /// # Unsafety
+    ///
+    /// Changing priority levels can break priority-based critical sections (see
+    /// [`register::basepri`](crate::register::basepri)) and compromise memory safety.
+    #[inline]
+    pub unsafe fn set_logical_priority(
+        &mut self,
+        system_handler: SystemHandler,
+        scheme: &PriorityScheme,
+        priority: LogicalPriority,
+    ) -> Result<(), PriorityError> {
+        let priority = scheme.encode(priority)?;
+        self.set_priority(system_handler, priority);
+        Ok(())
+    }
+
     /// Return the bit position of the exception enable bit in the SHCSR register
     #[inline]
     #[cfg(not(any(armv6m, armv8m_base)))]
@@ -1102,3 +1661,851 @@ impl SCB {
         }
     }
 }
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+const SCB_ICSR_NMIPENDSET: u32 = 1 << 31;
+
+/// All the `Exception` variants, in vector order
+#[cfg(not(any(armv6m, armv8m_base)))]
+const EXCEPTIONS: &[Exception] = &[
+    Exception::NonMaskableInt,
+    Exception::HardFault,
+    Exception::MemoryManagement,
+    Exception::BusFault,
+    Exception::UsageFault,
+    #[cfg(any(armv8m, native))]
+    Exception::SecureFault,
+    Exception::SVCall,
+    Exception::DebugMonitor,
+    Exception::PendSV,
+    Exception::SysTick,
+];
+
+/// Snapshot of the active and pending state of the system handlers.
+///
+/// The active bits show which exceptions were nested when the snapshot was taken, the pending
+/// bits which ones were waiting to be taken.
+#[cfg(not(any(armv6m, armv8m_base)))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct SystemHandlerState {
+    /// System Handler Control and State
+    pub shcsr: u32,
+    /// Interrupt Control and State, holds the pending bits of NMI, PendSV and SysTick
+    pub icsr: u32,
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+impl SystemHandlerState {
+    /// Return the bit position of the exception active bit in the SHCSR register
+    #[inline]
+    fn active_shift(exception: Exception) -> Option<u32> {
+        match exception {
+            Exception::MemoryManagement => Some(0),
+            Exception::BusFault => Some(1),
+            #[cfg(armv8m)]
+            Exception::HardFault => Some(2),
+            Exception::UsageFault => Some(3),
+            #[cfg(armv8m_main)]
+            Exception::SecureFault => Some(4),
+            #[cfg(armv8m)]
+            Exception::NonMaskableInt => Some(5),
+            Exception::SVCall => Some(7),
+            Exception::DebugMonitor => Some(8),
+            Exception::PendSV => Some(10),
+            Exception::SysTick => Some(11),
+            // Every exception has an active bit on ARMv8-M Mainline
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Return the bit position of the exception pending bit in the SHCSR register
+    #[inline]
+    fn pending_shift(exception: Exception) -> Option<u32> {
+        match exception {
+            Exception::UsageFault => Some(12),
+            Exception::MemoryManagement => Some(13),
+            Exception::BusFault => Some(14),
+            Exception::SVCall => Some(15),
+            #[cfg(armv8m_main)]
+            Exception::SecureFault => Some(20),
+            #[cfg(armv8m)]
+            Exception::HardFault => Some(21),
+            _ => None,
+        }
+    }
+
+    /// Check if an exception was active
+    ///
+    /// The active state of `NonMaskableInt` and `HardFault` is only recorded on ARMv8-M,
+    /// `SecureFault` can not be read from Non-secure state. For those, this returns `false`.
+    #[inline]
+    pub fn is_active(&self, exception: Exception) -> bool {
+        match Self::active_shift(exception) {
+            Some(shift) => self.shcsr & (1 << shift) != 0,
+            None => false,
+        }
+    }
+
+    /// Check if an exception was pending
+    ///
+    /// The pending state of `NonMaskableInt`, `PendSV` and `SysTick` is read from ICSR. The
+    /// pending state of `HardFault` is only recorded on ARMv8-M and the one of `DebugMonitor` is
+    /// held in DEMCR, for those this returns `false`.
+    #[inline]
+    pub fn is_pending(&self, exception: Exception) -> bool {
+        let icsr_bit = match exception {
+            Exception::NonMaskableInt => Some(SCB_ICSR_NMIPENDSET),
+            Exception::PendSV => Some(SCB_ICSR_PENDSVSET),
+            Exception::SysTick => Some(SCB_ICSR_PENDSTSET),
+            _ => None,
+        };
+
+        match (icsr_bit, Self::pending_shift(exception)) {
+            (Some(bit), _) => self.icsr & bit != 0,
+            (None, Some(shift)) => self.shcsr & (1 << shift) != 0,
+            (None, None) => false,
+        }
+    }
+
+    /// Returns the exceptions that were active, in vector order
+    #[inline]
+    pub fn active(&self) -> impl Iterator<Item = Exception> + Clone + '_ {
+        EXCEPTIONS
+            .iter()
+            .copied()
+            .filter(move |&exception| self.is_active(exception))
+    }
+
+    /// Returns the exceptions that were pending, in vector order
+    #[inline]
+    pub fn pending(&self) -> impl Iterator<Item = Exception> + Clone + '_ {
+        EXCEPTIONS
+            .iter()
+            .copied()
+            .filter(move |&exception| self.is_pending(exception))
+    }
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+impl core::fmt::Debug for SystemHandlerState {
+    #[allow(clippy::missing_inline_in_public_items)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        struct List<I>(I);
+
+        impl<I: Iterator<Item = Exception> + Clone> core::fmt::Debug for List<I> {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_list().entries(self.0.clone()).finish()
+            }
+        }
+
+        f.debug_struct("SystemHandlerState")
+            .field("active", &List(self.active()))
+            .field("pending", &List(self.pending()))
+            .finish()
+    }
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+impl SCB {
+    /// Takes a snapshot of the active and pending state of the system handlers
+    #[inline]
+    pub fn system_handler_state(&self) -> SystemHandlerState {
+        SystemHandlerState {
+            shcsr: self.shcsr.read(),
+            icsr: self.icsr.read(),
+        }
+    }
+
+    /// Check if an exception is active
+    ///
+    /// See [`SystemHandlerState::is_active`] for the exceptions whose state can not be read.
+    #[inline]
+    pub fn is_active(&self, exception: Exception) -> bool {
+        self.system_handler_state().is_active(exception)
+    }
+
+    /// Check if an exception is pending
+    ///
+    /// See [`SystemHandlerState::is_pending`] for the exceptions whose state can not be read.
+    #[inline]
+    pub fn is_pending(&self, exception: Exception) -> bool {
+        self.system_handler_state().is_pending(exception)
+    }
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+mod fault_consts {
+    pub const SCB_CFSR_IACCVIOL: u32 = 1 << 0;
+    pub const SCB_CFSR_DACCVIOL: u32 = 1 << 1;
+    pub const SCB_CFSR_MUNSTKERR: u32 = 1 << 3;
+    pub const SCB_CFSR_MSTKERR: u32 = 1 << 4;
+    pub const SCB_CFSR_MLSPERR: u32 = 1 << 5;
+    pub const SCB_CFSR_MMARVALID: u32 = 1 << 7;
+
+    pub const SCB_CFSR_IBUSERR: u32 = 1 << 8;
+    pub const SCB_CFSR_PRECISERR: u32 = 1 << 9;
+    pub const SCB_CFSR_IMPRECISERR: u32 = 1 << 10;
+    pub const SCB_CFSR_UNSTKERR: u32 = 1 << 11;
+    pub const SCB_CFSR_STKERR: u32 = 1 << 12;
+    pub const SCB_CFSR_LSPERR: u32 = 1 << 13;
+    pub const SCB_CFSR_BFARVALID: u32 = 1 << 15;
+
+    pub const SCB_CFSR_UNDEFINSTR: u32 = 1 << 16;
+    pub const SCB_CFSR_INVSTATE: u32 = 1 << 17;
+    pub const SCB_CFSR_INVPC: u32 = 1 << 18;
+    pub const SCB_CFSR_NOCP: u32 = 1 << 19;
+    #[cfg(any(armv8m, native))]
+    pub const SCB_CFSR_STKOF: u32 = 1 << 20;
+    pub const SCB_CFSR_UNALIGNED: u32 = 1 << 24;
+    pub const SCB_CFSR_DIVBYZERO: u32 = 1 << 25;
+
+    pub const SCB_HFSR_VECTTBL: u32 = 1 << 1;
+    pub const SCB_HFSR_FORCED: u32 = 1 << 30;
+    pub const SCB_HFSR_DEBUGEVT: u32 = 1 << 31;
+
+    pub const SCB_DFSR_HALTED: u32 = 1 << 0;
+    pub const SCB_DFSR_BKPT: u32 = 1 << 1;
+    pub const SCB_DFSR_DWTTRAP: u32 = 1 << 2;
+    pub const SCB_DFSR_VCATCH: u32 = 1 << 3;
+    pub const SCB_DFSR_EXTERNAL: u32 = 1 << 4;
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+use self::fault_consts::*;
+
+/// Snapshot of the fault status and fault address registers.
+///
+/// The raw register values are kept as read, the `mem_manage`, `bus`, `usage`, `hard` and
+/// `debug` methods decode them.
+#[cfg(not(any(armv6m, armv8m_base)))]
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct FaultStatus {
+    /// Configurable Fault Status
+    pub cfsr: u32,
+    /// HardFault Status
+    pub hfsr: u32,
+    /// Debug Fault Status
+    pub dfsr: u32,
+    /// MemManage Fault Address
+    pub mmfar: u32,
+    /// BusFault Address
+    pub bfar: u32,
+    /// Auxiliary Fault Status, its content is implementation defined
+    pub afsr: u32,
+}
+
+/// MemManage fault flags (`MMFSR`, bits 0 to 7 of `CFSR`)
+#[cfg(not(any(armv6m, armv8m_base)))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct MemManageFault {
+    /// Instruction fetch from a location that does not permit execution (`IACCVIOL`)
+    pub instruction_access_violation: bool,
+    /// Load or store at a location that does not permit the operation (`DACCVIOL`)
+    pub data_access_violation: bool,
+    /// Unstacking on exception return caused an access violation (`MUNSTKERR`)
+    pub unstacking_error: bool,
+    /// Stacking on exception entry caused an access violation (`MSTKERR`)
+    pub stacking_error: bool,
+    /// Lazy floating-point state preservation caused an access violation (`MLSPERR`)
+    pub lazy_fp_error: bool,
+    /// Faulting address, only present when `MMFAR` is valid (`MMARVALID`)
+    pub address: Option<u32>,
+}
+
+/// BusFault flags (`BFSR`, bits 8 to 15 of `CFSR`)
+#[cfg(not(any(armv6m, armv8m_base)))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct BusFault {
+    /// Bus error on an instruction fetch (`IBUSERR`)
+    pub instruction_bus_error: bool,
+    /// Precise data bus error (`PRECISERR`)
+    pub precise_error: bool,
+    /// Imprecise data bus error (`IMPRECISERR`)
+    pub imprecise_error: bool,
+    /// Unstacking on exception return caused a bus error (`UNSTKERR`)
+    pub unstacking_error: bool,
+    /// Stacking on exception entry caused a bus error (`STKERR`)
+    pub stacking_error: bool,
+    /// Lazy floating-point state preservation caused a bus error (`LSPERR`)
+    pub lazy_fp_error: bool,
+    /// Faulting address, only present when `BFAR` is valid (`BFARVALID`)
+    pub address: Option<u32>,
+}
+
+/// UsageFault flags (`UFSR`, bits 16 to 31 of `CFSR`)
+#[cfg(not(any(armv6m, armv8m_base)))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct UsageFault {
+    /// Undefined instruction (`UNDEFINSTR`)
+    pub undefined_instruction: bool,
+    /// Instruction executed with an invalid EPSR value, such as the Thumb bit cleared
+    /// (`INVSTATE`)
+    pub invalid_state: bool,
+    /// Invalid `EXC_RETURN` value on exception return (`INVPC`)
+    pub invalid_pc: bool,
+    /// Access to a disabled or absent coprocessor (`NOCP`)
+    pub no_coprocessor: bool,
+    /// Stack pointer went below its stack limit register (`STKOF`, only on ARMv8-M)
+    #[cfg(any(armv8m, native))]
+    pub stack_overflow: bool,
+    /// Unaligned access while unaligned accesses trap (`UNALIGNED`)
+    pub unaligned: bool,
+    /// Integer division by zero while divisions by zero trap (`DIVBYZERO`)
+    pub divide_by_zero: bool,
+}
+
+/// HardFault flags (`HFSR`)
+#[cfg(not(any(armv6m, armv8m_base)))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct HardFault {
+    /// Bus error while reading the vector table (`VECTTBL`)
+    pub vector_table_read: bool,
+    /// Configurable fault escalated to HardFault (`FORCED`)
+    pub forced: bool,
+    /// Debug event while debug is disabled (`DEBUGEVT`)
+    pub debug_event: bool,
+}
+
+/// Debug event flags (`DFSR`)
+#[cfg(not(any(armv6m, armv8m_base)))]
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DebugFault {
+    /// Halt request from the debugger or a step (`HALTED`)
+    pub halted: bool,
+    /// Breakpoint (`BKPT`)
+    pub breakpoint: bool,
+    /// DWT watchpoint or trace event (`DWTTRAP`)
+    pub dwt_trap: bool,
+    /// Vector catch (`VCATCH`)
+    pub vector_catch: bool,
+    /// External debug request (`EXTERNAL`)
+    pub external: bool,
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+impl FaultStatus {
+    /// Decodes the MemManage fault flags.
+    #[inline]
+    pub fn mem_manage(&self) -> MemManageFault {
+        MemManageFault {
+            instruction_access_violation: self.cfsr & SCB_CFSR_IACCVIOL != 0,
+            data_access_violation: self.cfsr & SCB_CFSR_DACCVIOL != 0,
+            unstacking_error: self.cfsr & SCB_CFSR_MUNSTKERR != 0,
+            stacking_error: self.cfsr & SCB_CFSR_MSTKERR != 0,
+            lazy_fp_error: self.cfsr & SCB_CFSR_MLSPERR != 0,
+            address: if self.cfsr & SCB_CFSR_MMARVALID != 0 {
+                Some(self.mmfar)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Decodes the BusFault flags.
+    #[inline]
+    pub fn bus(&self) -> BusFault {
+        BusFault {
+            instruction_bus_error: self.cfsr & SCB_CFSR_IBUSERR != 0,
+            precise_error: self.cfsr & SCB_CFSR_PRECISERR != 0,
+            imprecise_error: self.cfsr & SCB_CFSR_IMPRECISERR != 0,
+            unstacking_error: self.cfsr & SCB_CFSR_UNSTKERR != 0,
+            stacking_error: self.cfsr & SCB_CFSR_STKERR != 0,
+            lazy_fp_error: self.cfsr & SCB_CFSR_LSPERR != 0,
+            address: if self.cfsr & SCB_CFSR_BFARVALID != 0 {
+                Some(self.bfar)
+            } else {
+                None
+            },
+        }
+    }
+
+    /// Decodes the UsageFault flags.
+    #[inline]
+    pub fn usage(&self) -> UsageFault {
+        UsageFault {
+            undefined_instruction: self.cfsr & SCB_CFSR_UNDEFINSTR != 0,
+            invalid_state: self.cfsr & SCB_CFSR_INVSTATE != 0,
+            invalid_pc: self.cfsr & SCB_CFSR_INVPC != 0,
+            no_coprocessor: self.cfsr & SCB_CFSR_NOCP != 0,
+            #[cfg(any(armv8m, native))]
+            stack_overflow: self.cfsr & SCB_CFSR_STKOF != 0,
+            unaligned: self.cfsr & SCB_CFSR_UNALIGNED != 0,
+            divide_by_zero: self.cfsr & SCB_CFSR_DIVBYZERO != 0,
+        }
+    }
+
+    /// Decodes the HardFault flags.
+    #[inline]
+    pub fn hard(&self) -> HardFault {
+        HardFault {
+            vector_table_read: self.hfsr & SCB_HFSR_VECTTBL != 0,
+            forced: self.hfsr & SCB_HFSR_FORCED != 0,
+            debug_event: self.hfsr & SCB_HFSR_DEBUGEVT != 0,
+        }
+    }
+
+    /// Decodes the debug event flags.
+    #[inline]
+    pub fn debug(&self) -> DebugFault {
+        DebugFault {
+            halted: self.dfsr & SCB_DFSR_HALTED != 0,
+            breakpoint: self.dfsr & SCB_DFSR_BKPT != 0,
+            dwt_trap: self.dfsr & SCB_DFSR_DWTTRAP != 0,
+            vector_catch: self.dfsr & SCB_DFSR_VCATCH != 0,
+            external: self.dfsr & SCB_DFSR_EXTERNAL != 0,
+        }
+    }
+
+    /// Returns `true` if no fault or debug event flag is set.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.cfsr == 0 && self.hfsr == 0 && self.dfsr == 0
+    }
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+impl core::fmt::Debug for FaultStatus {
+    #[allow(clippy::missing_inline_in_public_items)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("FaultStatus")
+            .field("mem_manage", &self.mem_manage())
+            .field("bus", &self.bus())
+            .field("usage", &self.usage())
+            .field("hard", &self.hard())
+            .field("debug", &self.debug())
+            .field("afsr", &self.afsr)
+            .finish()
+    }
+}
+
+/// Writes `name: FLAG FLAG at 0x...` if any of the flags is set.
+#[cfg(not(any(armv6m, armv8m_base)))]
+fn write_fault_flags(
+    f: &mut core::fmt::Formatter<'_>,
+    first: &mut bool,
+    name: &str,
+    flags: &[(bool, &str)],
+    address: Option<u32>,
+) -> core::fmt::Result {
+    if !flags.iter().any(|&(set, _)| set) && address.is_none() {
+        return Ok(());
+    }
+
+    if !*first {
+        f.write_str(", ")?;
+    }
+    *first = false;
+
+    f.write_str(name)?;
+    f.write_str(":")?;
+    for &(_, flag) in flags.iter().filter(|&&(set, _)| set) {
+        f.write_str(" ")?;
+        f.write_str(flag)?;
+    }
+    if let Some(address) = address {
+        write!(f, " at {:#010x}", address)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+impl core::fmt::Display for FaultStatus {
+    /// Lists the set flags by their register field names, for example
+    /// `HardFault: FORCED, BusFault: PRECISERR at 0x40001000`.
+    #[allow(clippy::missing_inline_in_public_items)]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_empty() {
+            return f.write_str("no fault");
+        }
+
+        let mut first = true;
+
+        let hard = self.hard();
+        write_fault_flags(
+            f,
+            &mut first,
+            "HardFault",
+            &[
+                (hard.vector_table_read, "VECTTBL"),
+                (hard.forced, "FORCED"),
+                (hard.debug_event, "DEBUGEVT"),
+            ],
+            None,
+        )?;
+
+        let mem_manage = self.mem_manage();
+        write_fault_flags(
+            f,
+            &mut first,
+            "MemManage",
+            &[
+                (mem_manage.instruction_access_violation, "IACCVIOL"),
+                (mem_manage.data_access_violation, "DACCVIOL"),
+                (mem_manage.unstacking_error, "MUNSTKERR"),
+                (mem_manage.stacking_error, "MSTKERR"),
+                (mem_manage.lazy_fp_error, "MLSPERR"),
+            ],
+            mem_manage.address,
+        )?;
+
+        let bus = self.bus();
+        write_fault_flags(
+            f,
+            &mut first,
+            "BusFault",
+            &[
+                (bus.instruction_bus_error, "IBUSERR"),
+                (bus.precise_error, "PRECISERR"),
+                (bus.imprecise_error, "IMPRECISERR"),
+                (bus.unstacking_error, "UNSTKERR"),
+                (bus.stacking_error, "STKERR"),
+                (bus.lazy_fp_error, "LSPERR"),
+            ],
+            bus.address,
+        )?;
+
+        let usage = self.usage();
+        write_fault_flags(
+            f,
+            &mut first,
+            "UsageFault",
+            &[
+                (usage.undefined_instruction, "UNDEFINSTR"),
+                (usage.invalid_state, "INVSTATE"),
+                (usage.invalid_pc, "INVPC"),
+                (usage.no_coprocessor, "NOCP"),
+                #[cfg(any(armv8m, native))]
+                (usage.stack_overflow, "STKOF"),
+                (usage.unaligned, "UNALIGNED"),
+                (usage.divide_by_zero, "DIVBYZERO"),
+            ],
+            None,
+        )?;
+
+        let debug = self.debug();
+        write_fault_flags(
+            f,
+            &mut first,
+            "Debug",
+            &[
+                (debug.halted, "HALTED"),
+                (debug.breakpoint, "BKPT"),
+                (debug.dwt_trap, "DWTTRAP"),
+                (debug.vector_catch, "VCATCH"),
+                (debug.external, "EXTERNAL"),
+            ],
+            None,
+        )
+    }
+}
+
+#[cfg(not(any(armv6m, armv8m_base)))]
+impl SCB {
+    /// Reads the fault status and fault address registers.
+    ///
+    /// A fault address is only reported when its valid bit is set in `CFSR`.
+    #[inline]
+    pub fn fault_status() -> FaultStatus {
+        // NOTE(unsafe) atomic reads with no side effects
+        let scb = unsafe { &*SCB::PTR };
+
+        FaultStatus {
+            cfsr: scb.cfsr.read(),
+            hfsr: scb.hfsr.read(),
+            dfsr: scb.dfsr.read(),
+            mmfar: scb.mmfar.read(),
+            bfar: scb.bfar.read(),
+            afsr: scb.afsr.read(),
+        }
+    }
+
+    /// Clears the fault and debug event flags that are set in `status`.
+    ///
+    /// The status registers are write-one-to-clear, so flags raised after `status` was read are
+    /// kept. `AFSR` is implementation defined and is not modified.
+    #[inline]
+    pub fn clear_fault_status(&mut self, status: &FaultStatus) {
+        unsafe {
+            self.cfsr.write(status.cfsr);
+            self.hfsr.write(status.hfsr);
+            self.dfsr.write(status.dfsr);
+        }
+    }
+}
+
+/// Vector table with `N` entries that can be installed at runtime with
+/// [`SCB::set_vector_table`].
+///
+/// Entry 0 holds the initial stack pointer, entries 1 to 15 the system exception handlers and
+/// entry `16 + n` the handler of interrupt `n`.
+///
+/// The table is aligned like `A`. The alignment must be at least the size of the table rounded
+/// up to the next power of two, with a minimum of 128 bytes; this is checked at compile time.
+/// The default [`Align128`] is enough for up to 32 entries, bigger tables use one of the other
+/// `Align*` types:
+///
+/// ``` no_run
+/// use cortex_m::peripheral::scb::{Align256, VectorTable};
+///
+/// // 16 system exceptions and 48 interrupts, 256 bytes
+/// static TABLE: VectorTable<64, Align256> = VectorTable::new();
+/// ```
+#[repr(C)]
+pub struct VectorTable<const N: usize, A = Align128> {
+    _align: [A; 0],
+    entries: UnsafeCell<[usize; N]>,
+}
+
+// NOTE(unsafe) entries are only accessed with volatile word-sized reads and writes
+unsafe impl<const N: usize, A> Sync for VectorTable<N, A> {}
+
+/// 128 bytes alignment of a [`VectorTable`]
+#[repr(align(128))]
+#[derive(Clone, Copy, Debug)]
+pub struct Align128;
+
+/// 256 bytes alignment of a [`VectorTable`]
+#[repr(align(256))]
+#[derive(Clone, Copy, Debug)]
+pub struct Align256;
+
+/// 512 bytes alignment of a [`VectorTable`]
+#[repr(align(512))]
+#[derive(Clone, Copy, Debug)]
+pub struct Align512;
+
+/// 1024 bytes alignment of a [`VectorTable`]
+#[repr(align(1024))]
+#[derive(Clone, Copy, Debug)]
+pub struct Align1024;
+
+/// 2048 bytes alignment of a [`VectorTable`], enough for the largest vector table of 512 entries
+#[repr(align(2048))]
+#[derive(Clone, Copy, Debug)]
+pub struct Align2048;
+
+/// Alignment required by a vector table of `entries` entries: its size rounded up to the next
+/// power of two, with a minimum of 128 bytes.
+const fn vector_table_alignment(entries: usize) -> usize {
+    let alignment = (entries * 4).next_power_of_two();
+    if alignment < 128 {
+        128
+    } else {
+        alignment
+    }
+}
+
+/// Possible error values returned by the vector table methods.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum VectorTableError {
+    /// The table is not aligned to the next power of two of the size of the vector table of
+    /// this core, with a minimum of 128 bytes, or a [`VectorTable`] has fewer entries than the
+    /// vector table of this core.
+    Misaligned,
+    /// `VTOR` is not implemented on this core.
+    NotImplemented,
+    /// The entry does not fit in the table.
+    EntryOutOfRange,
+}
+
+impl<const N: usize, A> VectorTable<N, A> {
+    const HAS_SYSTEM_EXCEPTIONS: () = assert!(N >= 16, "a vector table has at least 16 entries");
+    const IS_ALIGNED: () = assert!(
+        mem::align_of::<A>() >= vector_table_alignment(N),
+        "the vector table is not aligned to its size rounded up to a power of two"
+    );
+
+    /// Creates a vector table where all the entries are zero.
+    ///
+    /// The table must be filled, for example with [`VectorTable::copy_from`], before being
+    /// installed.
+    #[inline]
+    pub const fn new() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::HAS_SYSTEM_EXCEPTIONS;
+        #[allow(clippy::let_unit_value)]
+        let () = Self::IS_ALIGNED;
+
+        VectorTable {
+            _align: [],
+            entries: UnsafeCell::new([0; N]),
+        }
+    }
+
+    /// Returns the entry at position `index`.
+    #[inline]
+    pub fn entry(&self, index: usize) -> Option<usize> {
+        if index < N {
+            // NOTE(unsafe) in bounds aligned read
+            Some(unsafe { ptr::read_volatile((self.entries.get() as *const usize).add(index)) })
+        } else {
+            None
+        }
+    }
+
+    /// Copies `N` entries from the vector table at `source`, for example the one returned by
+    /// [`SCB::vector_table`].
+    ///
+    /// # Safety
+    ///
+    /// `source` must be valid for reads of `N` entries.
+    #[inline]
+    pub unsafe fn copy_from(&self, source: *const usize) {
+        for index in 0..N {
+            self.write(index, ptr::read_volatile(source.add(index)));
+        }
+    }
+
+    /// Installs the handler of a system exception.
+    #[inline]
+    pub fn set_exception_handler(
+        &self,
+        exception: Exception,
+        handler: extern "C" fn(),
+    ) -> Result<(), VectorTableError> {
+        // `irqn` is within `[-14, -1]`
+        self.set_handler((16 + isize::from(exception.irqn())) as usize, handler)
+    }
+
+    /// Installs the handler of an interrupt.
+    #[inline]
+    pub fn set_interrupt_handler<I: InterruptNumber>(
+        &self,
+        interrupt: I,
+        handler: extern "C" fn(),
+    ) -> Result<(), VectorTableError> {
+        self.set_handler(16 + usize::from(interrupt.number()), handler)
+    }
+
+    #[inline]
+    fn set_handler(&self, index: usize, handler: extern "C" fn()) -> Result<(), VectorTableError> {
+        if index >= N {
+            return Err(VectorTableError::EntryOutOfRange);
+        }
+
+        // NOTE(unsafe) in bounds; a single word write, the entry is never seen half-written
+        unsafe { self.write(index, handler as usize) };
+        Ok(())
+    }
+
+    #[inline]
+    unsafe fn write(&self, index: usize, value: usize) {
+        ptr::write_volatile((self.entries.get() as *mut usize).add(index), value);
+    }
+}
+
+impl<const N: usize, A> Default for VectorTable<N, A> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SCB {
+    /// Returns the address of the active vector table.
+    ///
+    /// On cores without `VTOR` the vector table is always at address 0.
+    #[inline]
+    pub fn vector_table(&self) -> *const usize {
+        self.vtor.read() as usize as *const usize
+    }
+
+    /// Returns `true` if `VTOR` is implemented.
+    ///
+    /// `VTOR` is always implemented on ARMv7-M and ARMv8-M Mainline. It is not implemented on the
+    /// Cortex-M0, and optional on the Cortex-M0+ and ARMv8-M Baseline cores: on those, if `VTOR`
+    /// is zero, it is probed by writing a non-zero offset and reading it back. The probe runs
+    /// with interrupts disabled, but a fault or an NMI taken during the probe would be vectored
+    /// through the probed address.
+    #[inline]
+    pub fn has_vtor(&mut self) -> bool {
+        if cfg!(not(any(armv6m, armv8m_base))) || self.vtor.read() != 0 {
+            return true;
+        }
+
+        // Cortex-M0 does not implement VTOR.
+        const CPUID_PARTNO_CORTEX_M0: u32 = 0xC20;
+        // NOTE(unsafe) atomic read with no side effects
+        let partno = (unsafe { (*CPUID::PTR).base.read() } >> 4) & 0xFFF;
+        if cfg!(armv6m) && partno == CPUID_PARTNO_CORTEX_M0 {
+            return false;
+        }
+
+        crate::interrupt::free(|_| unsafe {
+            self.vtor.write(0x80);
+            let implemented = self.vtor.read() != 0;
+            self.vtor.write(0);
+            implemented
+        })
+    }
+
+    /// Installs a vector table by writing its address to `VTOR`.
+    ///
+    /// The alignment of the table to its own size is checked at compile time, see
+    /// [`VectorTable`]. The table must also have an entry for every interrupt line of the core
+    /// and be aligned to the size of the vector table of the core, see
+    /// [`SCB::vector_table_alignment`]; `VectorTableError::Misaligned` is returned otherwise.
+    ///
+    /// # Safety
+    ///
+    /// Every entry that can be taken, that is the system exceptions and all the enabled
+    /// interrupts, must point to a valid handler.
+    #[inline]
+    pub unsafe fn set_vector_table<const N: usize, A>(
+        &mut self,
+        table: &'static VectorTable<N, A>,
+    ) -> Result<(), VectorTableError> {
+        let address = table as *const VectorTable<N, A> as usize;
+        if N < 16 + NVIC::interrupt_lines() || address % Self::vector_table_alignment() != 0 {
+            return Err(VectorTableError::Misaligned);
+        }
+
+        self.install_vector_table(address)
+    }
+
+    /// Installs the vector table at `address` by writing it to `VTOR`.
+    ///
+    /// The address must be aligned to the size of the vector table of this core rounded up to the
+    /// next power of two, with a minimum of 128 bytes. The number of interrupts of the core is
+    /// read from `ICTR`, ARMv6-M cores are assumed to have 32 interrupts.
+    ///
+    /// # Safety
+    ///
+    /// `address` must point to a vector table that stays valid as long as it is installed, with
+    /// a valid handler for every entry that can be taken.
+    #[inline]
+    pub unsafe fn set_vector_table_address(
+        &mut self,
+        address: *const usize,
+    ) -> Result<(), VectorTableError> {
+        let address = address as usize;
+        if address % Self::vector_table_alignment() != 0 {
+            return Err(VectorTableError::Misaligned);
+        }
+
+        self.install_vector_table(address)
+    }
+
+    #[inline]
+    unsafe fn install_vector_table(&mut self, address: usize) -> Result<(), VectorTableError> {
+        if !self.has_vtor() {
+            return Err(VectorTableError::NotImplemented);
+        }
+
+        // Complete the writes to the table before it is used.
+        crate::asm::dsb();
+        self.vtor.write(address as u32);
+        crate::asm::dsb();
+        crate::asm::isb();
+
+        Ok(())
+    }
+
+    /// Returns the alignment required for the vector table of this core.
+    #[inline]
+    pub fn vector_table_alignment() -> usize {
+        vector_table_alignment(16 + NVIC::interrupt_lines())
+    }
+}
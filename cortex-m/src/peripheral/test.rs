@@ -14,6 +14,54 @@ fn cpuid() {
     assert_eq!(address(&cpuid.csselr), 0xE000_ED84);
 }
 
+#[test]
+fn control() {
+    use crate::register::control::{Control, Fpca, Npriv, Sfpa, Spsel};
+
+    // nPRIV, SPSEL, FPCA, SFPA, BTI_EN, UBTI_EN, PAC_EN, UPAC_EN and a reserved bit
+    let all = Control::from_bits(0x8000_00FF);
+    assert_eq!(all.sfpa(), Sfpa::Active);
+    assert!(all.bti_en() && all.ubti_en() && all.pac_en() && all.upac_en());
+
+    type Setter = fn(&mut Control, bool);
+    let bools: [(Setter, u32); 4] = [
+        (Control::set_bti_en, 1 << 4),
+        (Control::set_ubti_en, 1 << 5),
+        (Control::set_pac_en, 1 << 6),
+        (Control::set_upac_en, 1 << 7),
+    ];
+    for (set, mask) in bools {
+        let mut control = all;
+        set(&mut control, false);
+        assert_eq!(control.bits(), all.bits() & !mask);
+        set(&mut control, true);
+        assert_eq!(control.bits(), all.bits());
+
+        let mut control = Control::from_bits(0);
+        set(&mut control, true);
+        assert_eq!(control.bits(), mask);
+    }
+
+    let mut control = all;
+    control.set_sfpa(Sfpa::NotActive);
+    assert_eq!(control.bits(), 0x8000_00F7);
+    assert_eq!(control.sfpa(), Sfpa::NotActive);
+    control.set_sfpa(Sfpa::Active);
+    assert_eq!(control.bits(), all.bits());
+
+    // the other setters keep SFPA
+    let mut control = Control::from_bits(1 << 3);
+    control.set_npriv(Npriv::Unprivileged);
+    control.set_spsel(Spsel::Psp);
+    control.set_fpca(Fpca::Active);
+    assert_eq!(control.bits(), 0b1111);
+    control.set_npriv(Npriv::Privileged);
+    control.set_spsel(Spsel::Msp);
+    control.set_fpca(Fpca::NotActive);
+    assert_eq!(control.sfpa(), Sfpa::Active);
+    assert_eq!(control.bits(), 1 << 3);
+}
+
 #[test]
 fn dcb() {
     let dcb = unsafe { &*crate::peripheral::DCB::PTR };
@@ -54,6 +102,102 @@ fn dwt() {
     assert_eq!(address(&dwt.lsr), 0xE000_1FB4);
 }
 
+#[test]
+fn exception_frames() {
+    use crate::exception::{AdditionalStateContext, ExceptionFrame, ExtendedExceptionFrame};
+
+    // r0-r3, r12, lr, pc, xPSR with the padding bit set
+    let mut stack: [u32; 8] = [0, 1, 2, 3, 12, 0x0800_0101, 0x0800_0200, 0x0100_0200];
+    let frame = unsafe { ExceptionFrame::from_sp_mut(stack.as_mut_ptr()) };
+    assert_eq!(
+        [frame.r0(), frame.r1(), frame.r2(), frame.r3(), frame.r12()],
+        [0, 1, 2, 3, 12]
+    );
+    assert_eq!(frame.lr(), 0x0800_0101);
+    assert_eq!(frame.pc(), 0x0800_0200);
+    assert_eq!(frame.xpsr(), 0x0100_0200);
+    assert!(frame.is_padded());
+    frame.set_r0(42);
+    frame.set_r3(7);
+    assert_eq!(stack[0], 42);
+    assert_eq!(stack[3], 7);
+
+    let mut stack = [0u32; 26];
+    stack[7] = 0x0100_0000;
+    for (s, value) in stack[8..24].iter_mut().zip(100..) {
+        *s = value;
+    }
+    stack[24] = 0x0300_0000;
+    let frame = unsafe { ExtendedExceptionFrame::from_sp_mut(stack.as_mut_ptr()) };
+    assert!(!frame.basic().is_padded());
+    assert_eq!(frame.s()[0], 100);
+    assert_eq!(frame.s()[15], 115);
+    assert_eq!(frame.fpscr(), 0x0300_0000);
+    frame.set_s(15, 0x3F80_0000);
+    // out of range indexes are ignored
+    frame.set_s(16, 1);
+    frame.basic_mut().set_r1(5);
+    assert_eq!(stack[23], 0x3F80_0000);
+    assert_eq!(stack[24], 0x0300_0000);
+    assert_eq!(stack[1], 5);
+
+    let mut stack = [0u32; 10];
+    stack[0] = AdditionalStateContext::INTEGRITY_SIGNATURE;
+    stack[2..].copy_from_slice(&[4, 5, 6, 7, 8, 9, 10, 11]);
+    let context = unsafe { AdditionalStateContext::from_sp(stack.as_ptr()) };
+    assert!(context.is_signature_valid());
+    assert_eq!(context.r4_r11(), &[4, 5, 6, 7, 8, 9, 10, 11]);
+    stack[0] = AdditionalStateContext::INTEGRITY_SIGNATURE_FP;
+    assert!(unsafe { AdditionalStateContext::from_sp(stack.as_ptr()) }.is_signature_valid());
+    stack[0] = 0xFEFA_1258;
+    assert!(!unsafe { AdditionalStateContext::from_sp(stack.as_ptr()) }.is_signature_valid());
+}
+
+#[test]
+fn exc_return() {
+    use crate::exception::{ExcReturn, FrameType, Mode, SecurityState};
+    use crate::register::control::Spsel;
+
+    // Secure Thread mode on the PSP, basic frame
+    let exc = ExcReturn::from_bits(0xFFFF_FFFD).unwrap();
+    assert_eq!(exc.mode(), Mode::Thread);
+    assert_eq!(exc.stack(), Spsel::Psp);
+    assert_eq!(exc.frame_type(), FrameType::Basic);
+    assert_eq!(exc.exception_security(), SecurityState::Secure);
+    assert_eq!(exc.stack_security(), SecurityState::Secure);
+    assert!(exc.default_callee_stacking());
+
+    // Non-secure Handler mode on the MSP, extended frame
+    let exc = ExcReturn::from_bits(0xFFFF_FF80).unwrap();
+    assert_eq!(exc.mode(), Mode::Handler);
+    assert_eq!(exc.stack(), Spsel::Msp);
+    assert_eq!(exc.frame_type(), FrameType::Extended);
+    assert_eq!(exc.exception_security(), SecurityState::NonSecure);
+    assert_eq!(exc.stack_security(), SecurityState::NonSecure);
+    assert!(!exc.default_callee_stacking());
+
+    // invalid prefix
+    assert_eq!(ExcReturn::from_bits(0xEFFF_FFFD), None);
+    assert_eq!(ExcReturn::from_bits(0x0000_00FD), None);
+    // reserved bit 1 set, reserved bit 7 cleared
+    assert_eq!(ExcReturn::from_bits(0xFFFF_FFFF), None);
+    assert_eq!(ExcReturn::from_bits(0xFFFF_FF7D), None);
+    // Handler mode on the PSP
+    assert_eq!(ExcReturn::from_bits(0xFFFF_FFF5), None);
+
+    let exc = ExcReturn::new(Mode::Thread, Spsel::Psp, FrameType::Extended).unwrap();
+    assert_eq!(exc.bits(), 0xFFFF_FFAC);
+    assert_eq!(
+        ExcReturn::new(Mode::Handler, Spsel::Psp, FrameType::Basic),
+        None
+    );
+
+    let mut exc = ExcReturn::new(Mode::Handler, Spsel::Msp, FrameType::Basic).unwrap();
+    exc.set_exception_security(SecurityState::Secure);
+    exc.set_stack_security(SecurityState::Secure);
+    assert_eq!(exc.bits(), 0xFFFF_FFF1);
+}
+
 #[test]
 fn fpb() {
     let fpb = unsafe { &*crate::peripheral::FPB::PTR };
@@ -78,6 +222,36 @@ fn fpu() {
     assert_eq!(address(&fpu.mvfr[2]), 0xE000_EF48);
 }
 
+#[test]
+fn fpu_context() {
+    use crate::peripheral::fpu::{Fpccr, Fpdscr, FpuVariant, Mvfr};
+    use crate::register::fpscr::RMode;
+
+    // reset value: automatic and lazy preservation enabled
+    let mut fpccr = Fpccr::from_bits(0xC000_0000);
+    assert!(fpccr.aspen() && fpccr.lspen());
+    assert!(!fpccr.lspact());
+    fpccr.set_lspen(false);
+    assert_eq!(fpccr.bits(), 0x8000_0000);
+    assert!(Fpccr::from_bits(0x0000_0179).lspact());
+
+    let mut fpdscr = Fpdscr::from_bits(0);
+    fpdscr.set_dn(true);
+    fpdscr.set_fz(true);
+    fpdscr.set_rmode(RMode::MinusInfinity);
+    assert_eq!(fpdscr.bits(), 0x0380_0000);
+    assert_eq!(fpdscr.rmode(), RMode::MinusInfinity);
+    assert!(!fpdscr.ahp());
+
+    // Cortex-M4F (FPv4-SP) and Cortex-M7 (FPv5-DP)
+    let sp = Mvfr::from_words([0x1011_0021, 0x1100_0011, 0x0000_0040]);
+    assert_eq!(sp.variant(), Some(FpuVariant::SinglePrecision));
+    assert!(sp.fused_mac() && sp.square_root() && sp.divide());
+    let dp = Mvfr::from_words([0x1011_0221, 0x1200_0011, 0x0000_0040]);
+    assert_eq!(dp.variant(), Some(FpuVariant::DoublePrecision));
+    assert_eq!(Mvfr::from_words([0; 3]).variant(), None);
+}
+
 #[test]
 fn itm() {
     let itm = unsafe { &*crate::peripheral::ITM::PTR };
@@ -107,6 +281,389 @@ fn mpu() {
     assert_eq!(address(&mpu.rasr_a3), 0xE000EDB8);
 }
 
+#[test]
+fn mpu_region_encoding() {
+    use crate::peripheral::mpu::{
+        MpuAccessPermission, MpuCachePolicy, MpuError, MpuMemoryType, MpuRegion,
+    };
+
+    let region = MpuRegion {
+        base_address: 0x2000_0000,
+        size_log2: 16,
+        access: MpuAccessPermission::ReadWrite,
+        memory_type: MpuMemoryType::Normal {
+            outer: MpuCachePolicy::WriteBackWriteAllocate,
+            inner: MpuCachePolicy::WriteThrough,
+            shareable: true,
+        },
+        execute_never: true,
+        subregion_disable: 0b1000_0001,
+    };
+
+    let (rbar, rasr) = region.encode().unwrap();
+    assert_eq!(rbar, 0x2000_0000);
+    assert_eq!(rasr, 0x132E_811F);
+    assert_eq!(MpuRegion::decode(rbar, rasr), Ok(Some(region)));
+    assert_eq!(MpuRegion::decode(rbar, rasr & !1), Ok(None));
+
+    let misaligned = MpuRegion {
+        base_address: 0x2000_8000,
+        ..region
+    };
+    assert_eq!(misaligned.encode(), Err(MpuError::WrongBaseAddress));
+
+    let too_small = MpuRegion {
+        size_log2: 4,
+        ..region
+    };
+    assert_eq!(too_small.encode(), Err(MpuError::WrongSize));
+
+    let no_subregions = MpuRegion {
+        size_log2: 7,
+        ..region
+    };
+    assert_eq!(
+        no_subregions.encode(),
+        Err(MpuError::SubregionDisableNotSupported)
+    );
+}
+
+#[test]
+fn mpu_armv8_region_encoding() {
+    use crate::peripheral::mpu::armv8::{
+        MpuAccessPermission, MpuError, MpuRegion, MpuShareability,
+    };
+
+    let region = MpuRegion {
+        base_address: 0x2000_0000,
+        limit_address: 0x2000_FFFF,
+        shareability: MpuShareability::InnerShareable,
+        access: MpuAccessPermission::ReadOnly,
+        execute_never: true,
+        privileged_execute_never: true,
+        attribute_index: 3,
+    };
+    // RBAR: BASE, SH = 0b11, AP = 0b11, XN; RLAR: LIMIT, PXN, AttrIndx = 3, EN
+    assert_eq!(region.encode(), Ok((0x2000_001F, 0x2000_FFF7)));
+    assert_eq!(
+        MpuRegion::decode(0x2000_001F, 0x2000_FFF7),
+        Ok(Some(region))
+    );
+
+    let region = MpuRegion {
+        base_address: 0x0800_0000,
+        limit_address: 0x0807_FFFF,
+        shareability: MpuShareability::NonShareable,
+        access: MpuAccessPermission::PrivilegedReadWrite,
+        execute_never: false,
+        privileged_execute_never: false,
+        attribute_index: 0,
+    };
+    assert_eq!(region.encode(), Ok((0x0800_0000, 0x0807_FFE1)));
+    assert_eq!(
+        MpuRegion::decode(0x0800_0000, 0x0807_FFE1),
+        Ok(Some(region))
+    );
+
+    let encode = |shareability, access| {
+        MpuRegion {
+            shareability,
+            access,
+            ..region
+        }
+        .encode()
+        .unwrap()
+        .0
+    };
+    assert_eq!(
+        encode(
+            MpuShareability::OuterShareable,
+            MpuAccessPermission::ReadWrite
+        ),
+        0x0800_0012
+    );
+    assert_eq!(
+        encode(
+            MpuShareability::NonShareable,
+            MpuAccessPermission::PrivilegedReadOnly
+        ),
+        0x0800_0004
+    );
+
+    // alignment of the base and limit addresses, attribute index
+    let encode = |base_address, limit_address, attribute_index| {
+        MpuRegion {
+            base_address,
+            limit_address,
+            attribute_index,
+            ..region
+        }
+        .encode()
+    };
+    assert_eq!(
+        encode(0x0800_0010, 0x0807_FFFF, 0),
+        Err(MpuError::WrongBaseAddress)
+    );
+    assert_eq!(
+        encode(0x0800_0000, 0x0807_FFE0, 0),
+        Err(MpuError::WrongLimitAddress)
+    );
+    assert_eq!(
+        encode(0x0800_0020, 0x0800_001F, 0),
+        Err(MpuError::WrongLimitAddress)
+    );
+    assert_eq!(
+        encode(0x0800_0000, 0x0800_001F, 0),
+        Ok((0x0800_0000, 0x0800_0001))
+    );
+    assert_eq!(
+        encode(0x0800_0000, 0x0807_FFFF, 8),
+        Err(MpuError::WrongAttributeIndex)
+    );
+
+    // overlap with encoded regions: adjacent, overlapping by 32 bytes, disabled
+    let (rbar, rlar) = region.encode().unwrap();
+    let next = MpuRegion {
+        base_address: 0x0808_0000,
+        limit_address: 0x0808_FFFF,
+        ..region
+    };
+    assert!(region.overlaps(rbar, rlar));
+    assert!(!next.overlaps(rbar, rlar));
+    let next = MpuRegion {
+        base_address: 0x0807_FFE0,
+        ..next
+    };
+    assert!(next.overlaps(rbar, rlar));
+    assert!(!next.overlaps(rbar, rlar & !1));
+
+    // disabled region, reserved SH encoding
+    assert_eq!(MpuRegion::decode(0x0800_0000, 0x0807_FFE0), Ok(None));
+    assert_eq!(
+        MpuRegion::decode(0x0800_0008, 0x0807_FFE1),
+        Err(MpuError::UnsupportedAttributes)
+    );
+}
+
+#[test]
+fn mpu_armv8_memory_attribute() {
+    use crate::peripheral::mpu::armv8::{MemoryAttribute, MpuCachePolicy, MpuError};
+
+    let device = [
+        (MemoryAttribute::DeviceNGnRnE, 0x00),
+        (MemoryAttribute::DeviceNGnRE, 0x04),
+        (MemoryAttribute::DeviceNGRE, 0x08),
+        (MemoryAttribute::DeviceGRE, 0x0C),
+    ];
+    for (attribute, bits) in device {
+        assert_eq!(attribute.bits(), bits);
+        assert_eq!(MemoryAttribute::from_bits(bits), Ok(attribute));
+    }
+
+    let normal = [
+        (
+            MpuCachePolicy::NonCacheable,
+            MpuCachePolicy::NonCacheable,
+            0x44,
+        ),
+        (
+            MpuCachePolicy::WriteBack {
+                read_allocate: true,
+                write_allocate: true,
+            },
+            MpuCachePolicy::WriteBack {
+                read_allocate: true,
+                write_allocate: true,
+            },
+            0xFF,
+        ),
+        (
+            MpuCachePolicy::WriteThrough {
+                read_allocate: true,
+                write_allocate: false,
+            },
+            MpuCachePolicy::NonCacheable,
+            0xA4,
+        ),
+        (
+            MpuCachePolicy::NonCacheable,
+            MpuCachePolicy::WriteBack {
+                read_allocate: false,
+                write_allocate: true,
+            },
+            0x4D,
+        ),
+    ];
+    for (outer, inner, bits) in normal {
+        let attribute = MemoryAttribute::Normal { outer, inner };
+        assert_eq!(attribute.bits(), bits);
+        assert_eq!(MemoryAttribute::from_bits(bits), Ok(attribute));
+    }
+
+    // UNPREDICTABLE Device encoding, transient policies
+    assert_eq!(
+        MemoryAttribute::from_bits(0x01),
+        Err(MpuError::UnsupportedAttributes)
+    );
+    assert_eq!(
+        MemoryAttribute::from_bits(0x34),
+        Err(MpuError::UnsupportedAttributes)
+    );
+    assert_eq!(
+        MemoryAttribute::from_bits(0x43),
+        Err(MpuError::UnsupportedAttributes)
+    );
+}
+
+#[test]
+fn mpu_context() {
+    use crate::peripheral::mpu::{
+        MpuAccessPermission, MpuContext, MpuError, MpuMemoryType, MpuRegion,
+    };
+
+    let region = MpuRegion {
+        base_address: 0x2000_0000,
+        size_log2: 12,
+        access: MpuAccessPermission::ReadWrite,
+        memory_type: MpuMemoryType::StronglyOrdered,
+        execute_never: true,
+        subregion_disable: 0,
+    };
+
+    let mut context = MpuContext::<3>::new(5);
+    assert_eq!(context, MpuContext::new(5));
+    context.set_region(1, region).unwrap();
+    assert_eq!(
+        context.set_region(3, region),
+        Err(MpuError::RegionNumberTooBig)
+    );
+    assert_ne!(context, MpuContext::new(5));
+    context.disable_region(1).unwrap();
+    assert_eq!(context, MpuContext::new(5));
+}
+
+#[test]
+fn mpu_region_plan() {
+    use crate::peripheral::mpu::{plan_regions, MpuPlanError, MpuPlanMode, MpuRegionLayout};
+
+    // Bitmap of the 32-byte blocks of the first 4 KiB covered by the regions.
+    fn covered(regions: &[MpuRegionLayout]) -> u128 {
+        let mut blocks = 0;
+        for region in regions {
+            let base = u64::from(region.base_address);
+            let size = 1u64 << region.size_log2;
+            for block in 0..128 {
+                let address = block * 32;
+                if address < base || address >= base + size {
+                    continue;
+                }
+                let subregion = (address - base) / (size / 8);
+                if region.size_log2 < 8 || region.subregion_disable & (1 << subregion) == 0 {
+                    assert_eq!(blocks & (1 << block), 0, "regions overlap");
+                    blocks |= 1 << block;
+                }
+            }
+        }
+        blocks
+    }
+
+    fn blocks(start: u64, end: u64) -> u128 {
+        (start..end).fold(0, |blocks, block| blocks | (1 << block))
+    }
+
+    // Whether the blocks `[start, end)` can be covered by a single region.
+    fn single_region(start: u64, end: u64) -> bool {
+        (0..8).any(|subregion_log2| {
+            let subregion = 1 << subregion_log2;
+            start % subregion == 0
+                && end % subregion == 0
+                && (end - start == subregion
+                    || start / (8 * subregion) == (end - 1) / (8 * subregion))
+        })
+    }
+
+    for start in 0..128 {
+        for end in start + 1..=128 {
+            let plan = plan_regions(
+                start as u32 * 32,
+                (end - start) as u32 * 32,
+                16,
+                MpuPlanMode::Exact,
+            )
+            .unwrap();
+            assert_eq!(covered(plan.regions()), blocks(start, end));
+            assert_eq!(plan.regions().len() == 1, single_region(start, end));
+            assert!(plan.regions().len() <= 4);
+        }
+    }
+
+    // A stack covering most of a 4 KiB block only needs one region.
+    let plan = plan_regions(0x2000_0200, 0xE00, 1, MpuPlanMode::Exact).unwrap();
+    assert_eq!(
+        plan.regions(),
+        &[MpuRegionLayout {
+            base_address: 0x2000_0000,
+            size_log2: 12,
+            subregion_disable: 0b0000_0001,
+        }]
+    );
+    assert_eq!(plan.covered_range(), Some(0x2000_0200..=0x2000_0FFF));
+
+    assert_eq!(
+        plan_regions(0x2000_0020, 0x3C0, 1, MpuPlanMode::Exact),
+        Err(MpuPlanError::RegionBudgetExceeded)
+    );
+    assert_eq!(
+        plan_regions(0x2000_0010, 0x100, 4, MpuPlanMode::Exact),
+        Err(MpuPlanError::Unaligned)
+    );
+    assert_eq!(
+        plan_regions(0xFFFF_FF00, 0x200, 4, MpuPlanMode::Outward),
+        Err(MpuPlanError::AddressOverflow)
+    );
+    assert_eq!(
+        plan_regions(0x2000_0010, 0, 4, MpuPlanMode::Outward)
+            .unwrap()
+            .covered_range(),
+        None
+    );
+
+    for start in (0..2048).step_by(24) {
+        for end in (start + 1..=2048).step_by(40) {
+            for max_regions in 1..=3 {
+                let outward =
+                    plan_regions(start, end - start, max_regions, MpuPlanMode::Outward).unwrap();
+                let range = outward.covered_range().unwrap();
+                assert!(outward.regions().len() <= max_regions);
+                assert!(*range.start() <= start && *range.end() >= end - 1);
+                assert_eq!(
+                    covered(outward.regions()),
+                    blocks(
+                        u64::from(*range.start()) / 32,
+                        (u64::from(*range.end()) + 1) / 32
+                    )
+                );
+
+                let inward =
+                    plan_regions(start, end - start, max_regions, MpuPlanMode::Inward).unwrap();
+                assert!(inward.regions().len() <= max_regions);
+                if let Some(range) = inward.covered_range() {
+                    assert!(*range.start() >= start && *range.end() < end);
+                    assert_eq!(
+                        covered(inward.regions()),
+                        blocks(
+                            u64::from(*range.start()) / 32,
+                            (u64::from(*range.end()) + 1) / 32
+                        )
+                    );
+                } else {
+                    assert!(inward.regions().is_empty());
+                }
+            }
+        }
+    }
+}
+
 #[test]
 fn nvic() {
     let nvic = unsafe { &*crate::peripheral::NVIC::PTR };
@@ -121,6 +678,165 @@ fn nvic() {
     assert_eq!(address(&nvic.stir), 0xE000EF00);
 }
 
+#[test]
+fn nvic_priority_scheme() {
+    use crate::peripheral::nvic::{LogicalPriority, PriorityError, PriorityScheme};
+
+    // 4 implemented bits, 2 preemption priority bits and 2 subpriority bits
+    let scheme = PriorityScheme::new(4, 5).unwrap();
+    assert_eq!((scheme.preempt_bits(), scheme.sub_bits()), (2, 2));
+
+    let priority = LogicalPriority { preempt: 2, sub: 1 };
+    assert_eq!(scheme.encode(priority), Ok(0b1001_0000));
+    assert_eq!(scheme.decode(0b1001_0000), priority);
+    assert_eq!(scheme.decode(0b1001_1111), priority);
+    assert_eq!(
+        scheme.encode(LogicalPriority { preempt: 4, sub: 0 }),
+        Err(PriorityError::PreemptPriorityTooBig)
+    );
+    assert_eq!(
+        scheme.encode(LogicalPriority { preempt: 0, sub: 4 }),
+        Err(PriorityError::SubpriorityTooBig)
+    );
+
+    // Without grouping, all the implemented bits are preemption priority bits
+    let scheme = PriorityScheme::new(3, 0).unwrap();
+    assert_eq!((scheme.preempt_bits(), scheme.sub_bits()), (3, 0));
+    assert_eq!(
+        scheme.encode(LogicalPriority { preempt: 7, sub: 0 }),
+        Ok(0xE0)
+    );
+
+    // All the bits are subpriority bits
+    let scheme = PriorityScheme::new(8, 7).unwrap();
+    assert_eq!(
+        scheme.encode(LogicalPriority {
+            preempt: 0,
+            sub: 0xAB
+        }),
+        Ok(0xAB)
+    );
+    assert_eq!(
+        scheme.decode(0xAB),
+        LogicalPriority {
+            preempt: 0,
+            sub: 0xAB
+        }
+    );
+
+    assert_eq!(PriorityScheme::new(0, 0), None);
+    assert_eq!(PriorityScheme::new(4, 8), None);
+}
+
+#[test]
+fn nvic_interrupts() {
+    use crate::peripheral::nvic::{Interrupts, Scan};
+
+    let words = [0x8000_0001, 0, 0x0000_0110, 0xFFFF_FFFF];
+
+    let mut interrupts = Interrupts::<u16>::with_words(Scan::Pending, 3);
+    let mut numbers = [0; 8];
+    let mut count = 0;
+    while let Some(nr) = interrupts.next_number(|word| words[word]) {
+        numbers[count] = nr;
+        count += 1;
+    }
+    // the fourth register is not read
+    assert_eq!(numbers[..count], [0, 31, 68, 72]);
+
+    let mut interrupts = Interrupts::<u16>::with_words(Scan::Enabled, 4);
+    assert_eq!(interrupts.next_number(|_| 0), None);
+    let mut interrupts = Interrupts::<u16>::with_words(Scan::Enabled, 0);
+    assert_eq!(interrupts.next_number(|_| unreachable!()), None);
+}
+
+#[test]
+fn nvic_mask() {
+    use crate::interrupt::InterruptNumber;
+    use crate::peripheral::nvic::NvicMask;
+
+    #[derive(Clone, Copy)]
+    struct Interrupt(u16);
+
+    unsafe impl InterruptNumber for Interrupt {
+        fn number(self) -> u16 {
+            self.0
+        }
+    }
+
+    let mut mask: NvicMask = [Interrupt(0), Interrupt(33), Interrupt(479)]
+        .into_iter()
+        .collect();
+    assert!(mask.contains(Interrupt(33)));
+    assert!(!mask.contains(Interrupt(32)));
+    assert_eq!(mask.words()[0], 1);
+    assert_eq!(mask.words()[1], 1 << 1);
+    assert_eq!(mask.words()[14], 1 << 31);
+
+    mask.remove(Interrupt(0));
+    let mut other = NvicMask::new();
+    other.insert(Interrupt(33));
+    other.insert(Interrupt(40));
+    assert_eq!(mask.intersection(&other).words()[1], 1 << 1);
+    assert!(mask.intersection(&NvicMask::new()).is_empty());
+    assert!(!mask.is_empty());
+}
+
+#[test]
+fn psr_decoding() {
+    use crate::peripheral::scb::{Exception, VectActive};
+    use crate::register::{epsr::Epsr, ipsr::Ipsr, xpsr::Xpsr};
+
+    let thread = Ipsr::from_bits(0);
+    assert!(thread.is_thread_mode() && !thread.is_handler_mode());
+    assert_eq!(thread.vect_active(), Some(VectActive::ThreadMode));
+
+    let exceptions = [
+        (2, Exception::NonMaskableInt),
+        (3, Exception::HardFault),
+        (4, Exception::MemoryManagement),
+        (5, Exception::BusFault),
+        (6, Exception::UsageFault),
+        (7, Exception::SecureFault),
+        (11, Exception::SVCall),
+        (12, Exception::DebugMonitor),
+        (14, Exception::PendSV),
+        (15, Exception::SysTick),
+    ];
+    for (number, exception) in exceptions {
+        let ipsr = Ipsr::from_bits(number);
+        assert!(ipsr.is_handler_mode());
+        assert_eq!(ipsr.exception_number(), number as u16);
+        assert_eq!(ipsr.vect_active(), Some(VectActive::Exception(exception)));
+    }
+    assert_eq!(
+        Ipsr::from_bits(16 + 42).vect_active(),
+        Some(VectActive::Interrupt { irqn: 42 })
+    );
+    assert_eq!(Ipsr::from_bits(8).vect_active(), None);
+
+    // IT state 0b1011_0110: IT[7:2] in EPSR[15:10], IT[1:0] in EPSR[26:25]
+    let epsr = Epsr::from_bits(0x0500_B400);
+    assert!(epsr.t());
+    assert_eq!(epsr.ici_it(), 0b1011_0110);
+    assert!(epsr.in_it_block());
+
+    // ICI state of an interrupted LDM/STM, continuing from register 5
+    let epsr = Epsr::from_bits(0x0100_5000);
+    assert_eq!(epsr.ici_it(), 0b0101_0000);
+    assert!(!epsr.in_it_block());
+
+    // N and C flags, Thumb state, IRQ 5
+    let xpsr = Xpsr::from_bits(0xA100_0015);
+    assert!(xpsr.apsr().n() && xpsr.apsr().c());
+    assert!(!xpsr.apsr().z() && !xpsr.apsr().v());
+    assert_eq!(
+        xpsr.ipsr().vect_active(),
+        Some(VectActive::Interrupt { irqn: 5 })
+    );
+    assert!(xpsr.epsr().t() && !xpsr.epsr().in_it_block());
+}
+
 #[test]
 fn scb() {
     let scb = unsafe { &*crate::peripheral::SCB::PTR };
@@ -141,6 +857,155 @@ fn scb() {
     assert_eq!(address(&scb.cpacr), 0xE000_ED88);
 }
 
+#[test]
+fn scb_ccr() {
+    use crate::peripheral::scb::Ccr;
+
+    // STKALIGN (9), BFHFNMIGN (8), DIV_0_TRP (4), UNALIGN_TRP (3), USERSETMPEND (1),
+    // NONBASETHRDENA (0), and the cache enable bits
+    let all = Ccr::from_bits(0x0003_031B);
+    assert!(all.stkalign() && all.bfhfnmign() && all.div_0_trp());
+    assert!(all.unalign_trp() && all.usersetmpend() && all.nonbasethrdena());
+
+    type Setter = fn(&mut Ccr, bool);
+    let bits: [(Setter, u32); 6] = [
+        (Ccr::set_nonbasethrdena, 1 << 0),
+        (Ccr::set_usersetmpend, 1 << 1),
+        (Ccr::set_unalign_trp, 1 << 3),
+        (Ccr::set_div_0_trp, 1 << 4),
+        (Ccr::set_bfhfnmign, 1 << 8),
+        (Ccr::set_stkalign, 1 << 9),
+    ];
+    for (set, mask) in bits {
+        let mut ccr = all;
+        set(&mut ccr, false);
+        assert_eq!(ccr.bits(), all.bits() & !mask);
+        set(&mut ccr, true);
+        assert_eq!(ccr.bits(), all.bits());
+
+        let mut ccr = Ccr::from_bits(0);
+        set(&mut ccr, true);
+        assert_eq!(ccr.bits(), mask);
+    }
+
+    let ccr = Ccr::from_bits(1 << 9);
+    assert!(ccr.stkalign());
+    assert!(!ccr.bfhfnmign() && !ccr.div_0_trp() && !ccr.unalign_trp());
+    assert!(!ccr.usersetmpend() && !ccr.nonbasethrdena());
+}
+
+#[test]
+fn scb_fault_status() {
+    use crate::peripheral::scb::{BusFault, FaultStatus};
+    use core::fmt::Write;
+
+    struct Buffer([u8; 128], usize);
+
+    impl Write for Buffer {
+        fn write_str(&mut self, s: &str) -> core::fmt::Result {
+            self.0[self.1..self.1 + s.len()].copy_from_slice(s.as_bytes());
+            self.1 += s.len();
+            Ok(())
+        }
+    }
+
+    let status = FaultStatus {
+        cfsr: 0x0010_8202,
+        hfsr: 0x4000_0000,
+        dfsr: 0,
+        mmfar: 0x2000_0100,
+        bfar: 0x4000_1000,
+        afsr: 0,
+    };
+
+    assert!(!status.is_empty());
+    assert!(status.hard().forced);
+    assert!(status.mem_manage().data_access_violation);
+    assert_eq!(status.mem_manage().address, None);
+    assert_eq!(
+        status.bus(),
+        BusFault {
+            precise_error: true,
+            address: Some(0x4000_1000),
+            ..BusFault::default()
+        }
+    );
+    assert!(status.usage().stack_overflow);
+
+    let mut buffer = Buffer([0; 128], 0);
+    write!(buffer, "{}", status).unwrap();
+    assert_eq!(
+        core::str::from_utf8(&buffer.0[..buffer.1]).unwrap(),
+        "HardFault: FORCED, MemManage: DACCVIOL, BusFault: PRECISERR at 0x40001000, UsageFault: STKOF"
+    );
+}
+
+#[test]
+fn scb_system_handler_state() {
+    use crate::peripheral::scb::{Exception, SystemHandlerState};
+
+    // UsageFault and SVCall active, MemManage pending, PendSV pending through ICSR
+    let state = SystemHandlerState {
+        shcsr: (1 << 3) | (1 << 7) | (1 << 13) | (1 << 18),
+        icsr: 1 << 28,
+    };
+
+    assert!(state.is_active(Exception::UsageFault));
+    assert!(state.is_active(Exception::SVCall));
+    assert!(!state.is_active(Exception::MemoryManagement));
+    assert!(state.is_pending(Exception::MemoryManagement));
+    assert!(state.is_pending(Exception::PendSV));
+    assert!(!state.is_pending(Exception::SysTick));
+    assert!(!state.is_pending(Exception::UsageFault));
+
+    let mut active = state.active();
+    assert_eq!(active.next(), Some(Exception::UsageFault));
+    assert_eq!(active.next(), Some(Exception::SVCall));
+    assert_eq!(active.next(), None);
+
+    let mut pending = state.pending();
+    assert_eq!(pending.next(), Some(Exception::MemoryManagement));
+    assert_eq!(pending.next(), Some(Exception::PendSV));
+    assert_eq!(pending.next(), None);
+}
+
+#[test]
+fn scb_vector_table() {
+    use crate::interrupt::InterruptNumber;
+    use crate::peripheral::scb::{Align2048, Align256, Exception, VectorTable, VectorTableError};
+
+    #[derive(Clone, Copy)]
+    struct Interrupt(u16);
+
+    unsafe impl InterruptNumber for Interrupt {
+        fn number(self) -> u16 {
+            self.0
+        }
+    }
+
+    extern "C" fn handler() {}
+
+    assert_eq!(core::mem::align_of::<VectorTable<32>>(), 128);
+    assert_eq!(core::mem::align_of::<VectorTable<64, Align256>>(), 256);
+    assert_eq!(core::mem::align_of::<VectorTable<512, Align2048>>(), 2048);
+    let _ = VectorTable::<64, Align256>::new();
+    let _ = VectorTable::<512, Align2048>::new();
+
+    let table = VectorTable::<20>::new();
+    table
+        .set_exception_handler(Exception::SysTick, handler)
+        .unwrap();
+    table.set_interrupt_handler(Interrupt(3), handler).unwrap();
+    assert_eq!(table.entry(15), Some(handler as *const () as usize));
+    assert_eq!(table.entry(19), Some(handler as *const () as usize));
+    assert_eq!(table.entry(14), Some(0));
+    assert_eq!(table.entry(20), None);
+    assert_eq!(
+        table.set_interrupt_handler(Interrupt(4), handler),
+        Err(VectorTableError::EntryOutOfRange)
+    );
+}
+
 #[test]
 fn syst() {
     let syst = unsafe { &*crate::peripheral::SYST::PTR };
@@ -10,12 +10,25 @@ pub struct Apsr {
 }
 
 impl Apsr {
+    /// Creates an `Apsr` from raw bits, for example from a stacked xPSR value
+    #[inline]
+    pub fn from_bits(bits: u32) -> Self {
+        Apsr { bits }
+    }
+
     /// Returns the contents of the register as raw bits
     #[inline]
     pub fn bits(self) -> u32 {
         self.bits
     }
 
+    /// Greater than or Equal flags, set by the SIMD instructions of the DSP extension
+    #[cfg(any(armv7em, armv8m_main, native))]
+    #[inline]
+    pub fn ge(self) -> u8 {
+        ((self.bits >> 16) & 0xF) as u8
+    }
+
     /// DSP overflow and saturation flag
     #[inline]
     pub fn q(self) -> bool {
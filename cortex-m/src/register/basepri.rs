@@ -24,14 +24,15 @@ pub unsafe fn write(basepri: u8) {
         asm!(
             "mrs {1}, PRIMASK",
             "cpsid i",
-            "tst.w {1}, #1",
             "msr BASEPRI, {0}",
-            "it ne",
-            "bxne lr",
+            // keep interrupts disabled if they were disabled before
+            "tst.w {1}, #1",
+            "bne 1f",
             "cpsie i",
+            "1:",
             in(reg) basepri,
             out(reg) _,
-            options(nomem, nostack, preserves_flags),
+            options(nomem, nostack),
         );
     }
 
@@ -40,3 +41,23 @@ pub unsafe fn write(basepri: u8) {
         asm!("msr BASEPRI, {}", in(reg) basepri, options(nomem, nostack, preserves_flags));
     }
 }
+
+/// Reads the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will return zeroes.
+#[cfg(armv8m)]
+#[inline]
+pub fn read_ns() -> u8 {
+    let r;
+    unsafe { asm!("mrs {}, BASEPRI_NS", out(reg) r, options(nomem, nostack, preserves_flags)) };
+    r
+}
+
+/// Writes to the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will be ignored.
+#[cfg(armv8m)]
+#[inline]
+pub unsafe fn write_ns(basepri: u8) {
+    asm!("msr BASEPRI_NS, {}", in(reg) basepri, options(nomem, nostack, preserves_flags));
+}
@@ -19,14 +19,15 @@ pub fn write(basepri: u8) {
             asm!(
                 "mrs {1}, PRIMASK",
                 "cpsid i",
-                "tst.w {1}, #1",
                 "msr BASEPRI_MAX, {0}",
-                "it ne",
-                "bxne lr",
+                // keep interrupts disabled if they were disabled before
+                "tst.w {1}, #1",
+                "bne 1f",
                 "cpsie i",
+                "1:",
                 in(reg) basepri,
                 out(reg) _,
-                options(nomem, nostack, preserves_flags),
+                options(nomem, nostack),
             );
         }
     }
@@ -83,6 +83,98 @@ impl Control {
             Fpca::NotActive => self.bits &= !mask,
         }
     }
+
+    /// Whether the floating-point context belongs to the Secure state (only on ARMv8-M)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn sfpa(self) -> Sfpa {
+        if self.bits & (1 << 3) == (1 << 3) {
+            Sfpa::Active
+        } else {
+            Sfpa::NotActive
+        }
+    }
+
+    /// Sets the SFPA value (only on ARMv8-M).
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_sfpa(&mut self, sfpa: Sfpa) {
+        let mask = 1 << 3;
+        match sfpa {
+            Sfpa::Active => self.bits |= mask,
+            Sfpa::NotActive => self.bits &= !mask,
+        }
+    }
+
+    /// Whether Branch Target Identification is enforced in privileged mode (BTI_EN, only on
+    /// ARMv8.1-M with the PACBTI extension)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn bti_en(self) -> bool {
+        self.bits & (1 << 4) == (1 << 4)
+    }
+
+    /// Sets the BTI_EN value (only on ARMv8.1-M with the PACBTI extension).
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_bti_en(&mut self, enable: bool) {
+        self.set_bit(4, enable)
+    }
+
+    /// Whether Branch Target Identification is enforced in unprivileged mode (UBTI_EN, only on
+    /// ARMv8.1-M with the PACBTI extension)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn ubti_en(self) -> bool {
+        self.bits & (1 << 5) == (1 << 5)
+    }
+
+    /// Sets the UBTI_EN value (only on ARMv8.1-M with the PACBTI extension).
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_ubti_en(&mut self, enable: bool) {
+        self.set_bit(5, enable)
+    }
+
+    /// Whether pointer authentication is enabled in privileged mode (PAC_EN, only on ARMv8.1-M
+    /// with the PACBTI extension)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn pac_en(self) -> bool {
+        self.bits & (1 << 6) == (1 << 6)
+    }
+
+    /// Sets the PAC_EN value (only on ARMv8.1-M with the PACBTI extension).
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_pac_en(&mut self, enable: bool) {
+        self.set_bit(6, enable)
+    }
+
+    /// Whether pointer authentication is enabled in unprivileged mode (UPAC_EN, only on
+    /// ARMv8.1-M with the PACBTI extension)
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn upac_en(self) -> bool {
+        self.bits & (1 << 7) == (1 << 7)
+    }
+
+    /// Sets the UPAC_EN value (only on ARMv8.1-M with the PACBTI extension).
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    pub fn set_upac_en(&mut self, enable: bool) {
+        self.set_bit(7, enable)
+    }
+
+    #[cfg(any(armv8m, native))]
+    #[inline]
+    fn set_bit(&mut self, bit: u32, value: bool) {
+        if value {
+            self.bits |= 1 << bit;
+        } else {
+            self.bits &= !(1 << bit);
+        }
+    }
 }
 
 /// Thread mode privilege level
@@ -154,6 +246,31 @@ impl Fpca {
     }
 }
 
+/// Whether the floating-point context belongs to the Secure state
+#[cfg(any(armv8m, native))]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Sfpa {
+    /// The floating-point registers contain Secure state.
+    Active,
+    /// The floating-point registers do not contain Secure state.
+    NotActive,
+}
+
+#[cfg(any(armv8m, native))]
+impl Sfpa {
+    /// Do the floating-point registers contain Secure state?
+    #[inline]
+    pub fn is_active(self) -> bool {
+        self == Sfpa::Active
+    }
+
+    /// Do the floating-point registers not contain Secure state?
+    #[inline]
+    pub fn is_not_active(self) -> bool {
+        self == Sfpa::NotActive
+    }
+}
+
 /// Reads the CPU register
 #[cfg(cortex_m)]
 #[inline]
@@ -164,6 +281,10 @@ pub fn read() -> Control {
 }
 
 /// Writes to the CPU register.
+///
+/// An ISB is issued after the write, so that a change of SPSEL or nPRIV is visible to the
+/// following instructions. All the bits are written: use [`modify`] to keep the bits that are not
+/// modelled by the caller, such as SFPA, unchanged.
 #[cfg(cortex_m)]
 #[inline]
 pub unsafe fn write(control: Control) {
@@ -181,3 +302,44 @@ pub unsafe fn write(control: Control) {
     // Ensure memory accesses are not reordered around the CONTROL update.
     compiler_fence(Ordering::SeqCst);
 }
+
+/// Reads the CPU register, modifies it with `f` and writes it back, followed by an ISB.
+///
+/// Returns the written value. The sequence is not atomic: a change of nPRIV made by an exception
+/// handler that preempts it is overwritten.
+///
+/// # Safety
+///
+/// Same as [`write()`].
+#[cfg(cortex_m)]
+#[inline]
+pub unsafe fn modify<F>(f: F) -> Control
+where
+    F: FnOnce(&mut Control),
+{
+    let mut control = read();
+    f(&mut control);
+    write(control);
+    control
+}
+
+/// Reads the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will return zeroes.
+#[cfg(armv8m)]
+#[inline]
+pub fn read_ns() -> Control {
+    let bits;
+    unsafe { asm!("mrs {}, CONTROL_NS", out(reg) bits, options(nomem, nostack, preserves_flags)) };
+    Control { bits }
+}
+
+/// Writes to the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will be ignored.
+#[cfg(armv8m)]
+#[inline]
+pub unsafe fn write_ns(control: Control) {
+    let control = control.bits();
+    asm!("msr CONTROL_NS, {}", in(reg) control, options(nomem, nostack, preserves_flags));
+}
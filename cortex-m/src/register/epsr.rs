@@ -0,0 +1,68 @@
+//! Execution Program Status Register
+//!
+//! The EPSR bits always read as zero with `MRS`: `read` is only provided for completeness, the
+//! meaningful values come from a stacked xPSR, see
+//! [`ExceptionFrame::xpsr`](crate::exception::ExceptionFrame::xpsr) and [`Epsr::from_bits`].
+
+#[cfg(cortex_m)]
+use core::arch::asm;
+
+/// Execution Program Status Register
+#[derive(Clone, Copy, Debug)]
+pub struct Epsr {
+    bits: u32,
+}
+
+impl Epsr {
+    /// Creates an `Epsr` from raw bits, for example from a stacked xPSR value
+    #[inline]
+    pub fn from_bits(bits: u32) -> Self {
+        Epsr { bits }
+    }
+
+    /// Returns the contents of the register as raw bits
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    /// Thumb state bit, must be set for the processor to execute instructions
+    #[inline]
+    pub fn t(self) -> bool {
+        self.bits & (1 << 24) == (1 << 24)
+    }
+
+    /// Interrupt-continuable instruction or If-Then state bits (ICI/IT), in IT order: bits 7:2
+    /// come from bits 15:10 of the register and bits 1:0 from bits 26:25
+    ///
+    /// The state holds the position in an IT block, or the progress of an interrupted multiple
+    /// load or store instruction.
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn ici_it(self) -> u8 {
+        (((self.bits >> 8) & 0xFC) | ((self.bits >> 25) & 0x3)) as u8
+    }
+
+    /// Is the processor in an IT block?
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    pub fn in_it_block(self) -> bool {
+        // an ICI value has the low IT bits cleared, an IT block a non-zero IT[3:0]
+        self.ici_it() & 0xF != 0
+    }
+}
+
+/// Reads the CPU register
+///
+/// `MRS` reads the EPSR bits as zero, so the returned value is always zero; [`xpsr::read`]
+/// reads them as zero too. Decode a stacked xPSR with [`Epsr::from_bits`] or
+/// [`Xpsr::epsr`](super::xpsr::Xpsr::epsr) instead.
+///
+/// [`xpsr::read`]: super::xpsr::read
+#[cfg(cortex_m)]
+#[inline]
+pub fn read() -> Epsr {
+    let bits;
+    unsafe { asm!("mrs {}, EPSR", out(reg) bits, options(nomem, nostack, preserves_flags)) };
+    Epsr { bits }
+}
@@ -13,6 +13,17 @@ pub enum Faultmask {
 }
 
 impl Faultmask {
+    /// Decodes the value of the CPU register
+    #[cfg(cortex_m)]
+    #[inline]
+    fn from_bits(bits: u32) -> Self {
+        if bits & (1 << 0) == (1 << 0) {
+            Faultmask::Inactive
+        } else {
+            Faultmask::Active
+        }
+    }
+
     /// All exceptions are active
     #[inline]
     pub fn is_active(self) -> bool {
@@ -32,9 +43,29 @@ impl Faultmask {
 pub fn read() -> Faultmask {
     let r: u32;
     unsafe { asm!("mrs {}, FAULTMASK", out(reg) r, options(nomem, nostack, preserves_flags)) };
-    if r & (1 << 0) == (1 << 0) {
-        Faultmask::Inactive
-    } else {
-        Faultmask::Active
-    }
+    Faultmask::from_bits(r)
+}
+
+/// Reads the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will return zeroes.
+#[cfg(armv8m)]
+#[inline]
+pub fn read_ns() -> Faultmask {
+    let r: u32;
+    unsafe { asm!("mrs {}, FAULTMASK_NS", out(reg) r, options(nomem, nostack, preserves_flags)) };
+    Faultmask::from_bits(r)
+}
+
+/// Writes to the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will be ignored.
+#[cfg(armv8m)]
+#[inline]
+pub unsafe fn write_ns(faultmask: Faultmask) {
+    let bits: u32 = match faultmask {
+        Faultmask::Active => 0,
+        Faultmask::Inactive => 1,
+    };
+    asm!("msr FAULTMASK_NS, {}", in(reg) bits, options(nomem, nostack, preserves_flags));
 }
@@ -1,5 +1,6 @@
 //! Floating-point Status Control Register
 
+#[cfg(cortex_m)]
 use core::arch::asm;
 
 /// Floating-point Status Control Register
@@ -293,6 +294,7 @@ impl RMode {
 }
 
 /// Read the FPSCR register
+#[cfg(cortex_m)]
 #[inline]
 pub fn read() -> Fpscr {
     let r;
@@ -301,6 +303,7 @@ pub fn read() -> Fpscr {
 }
 
 /// Set the value of the FPSCR register
+#[cfg(cortex_m)]
 #[inline]
 pub unsafe fn write(fpscr: Fpscr) {
     let fpscr = fpscr.bits();
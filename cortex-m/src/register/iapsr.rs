@@ -0,0 +1,19 @@
+//! Interrupt and Application Program Status Register
+
+#[cfg(cortex_m)]
+use core::arch::asm;
+
+#[cfg(cortex_m)]
+use super::xpsr::Xpsr;
+
+/// Reads the CPU register
+///
+/// The IAPSR is the combination of the APSR and the IPSR, the EPSR bits of the returned value are
+/// zero.
+#[cfg(cortex_m)]
+#[inline]
+pub fn read() -> Xpsr {
+    let bits;
+    unsafe { asm!("mrs {}, IAPSR", out(reg) bits, options(nomem, nostack, preserves_flags)) };
+    Xpsr::from_bits(bits)
+}
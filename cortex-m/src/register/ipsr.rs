@@ -0,0 +1,69 @@
+//! Interrupt Program Status Register
+
+#[cfg(cortex_m)]
+use core::arch::asm;
+
+use crate::peripheral::scb::VectActive;
+
+/// Interrupt Program Status Register
+#[derive(Clone, Copy, Debug)]
+pub struct Ipsr {
+    bits: u32,
+}
+
+impl Ipsr {
+    /// Creates an `Ipsr` from raw bits, for example from a stacked xPSR value
+    #[inline]
+    pub fn from_bits(bits: u32) -> Self {
+        Ipsr { bits }
+    }
+
+    /// Returns the contents of the register as raw bits
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    /// Exception number of the running exception, 0 in Thread mode
+    #[inline]
+    pub fn exception_number(self) -> u16 {
+        (self.bits & 0x1FF) as u16
+    }
+
+    /// Running exception, `None` for a reserved exception number
+    #[inline]
+    pub fn vect_active(self) -> Option<VectActive> {
+        VectActive::from(self.exception_number())
+    }
+
+    /// Is the processor in Handler mode?
+    #[inline]
+    pub fn is_handler_mode(self) -> bool {
+        self.exception_number() != 0
+    }
+
+    /// Is the processor in Thread mode?
+    #[inline]
+    pub fn is_thread_mode(self) -> bool {
+        !self.is_handler_mode()
+    }
+}
+
+/// Reads the CPU register
+#[cfg(cortex_m)]
+#[inline]
+pub fn read() -> Ipsr {
+    let bits;
+    unsafe { asm!("mrs {}, IPSR", out(reg) bits, options(nomem, nostack, preserves_flags)) };
+    Ipsr { bits }
+}
+
+/// Is the processor running an exception handler?
+///
+/// Unlike [`SCB::vect_active`](crate::peripheral::SCB::vect_active), this does not access the
+/// System Control Block and also works from unprivileged code.
+#[cfg(cortex_m)]
+#[inline]
+pub fn is_in_handler_mode() -> bool {
+    read().is_handler_mode()
+}
@@ -4,8 +4,10 @@
 use core::arch::asm;
 
 /// Reads the CPU register
+///
+/// Always inlined, so that the value read is the one of the caller and not a return address.
 #[cfg(cortex_m)]
-#[inline]
+#[inline(always)]
 pub fn read() -> u32 {
     let r;
     unsafe { asm!("mov {}, lr", out(reg) r, options(nomem, nostack, preserves_flags)) };
@@ -12,9 +12,13 @@
 //! or UNPRIVILEGED, mode.
 //!
 //! - APSR
+//! - EPSR
+//! - IAPSR
+//! - IPSR
 //! - LR
 //! - PC
 //! - PSP
+//! - XPSR
 //!
 //! The following registers are NOT available on ARMv6-M devices
 //! (`thumbv6m-none-eabi`):
@@ -41,7 +45,7 @@ pub mod control;
 #[cfg(all(not(armv6m), not(armv8m_base)))]
 pub mod faultmask;
 
-#[cfg(has_fpu)]
+#[cfg(any(has_fpu, native))]
 pub mod fpscr;
 
 pub mod msp;
@@ -58,6 +62,14 @@ pub mod psplim;
 
 pub mod apsr;
 
+pub mod epsr;
+
+pub mod iapsr;
+
+pub mod ipsr;
+
+pub mod xpsr;
+
 pub mod lr;
 
 pub mod pc;
@@ -15,3 +15,21 @@ pub fn read() -> u32 {
 pub unsafe fn write(bits: u32) {
     asm!("msr MSPLIM, {}", in(reg) bits, options(nomem, nostack, preserves_flags));
 }
+
+/// Reads the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will return zeroes.
+#[inline]
+pub fn read_ns() -> u32 {
+    let r;
+    unsafe { asm!("mrs {}, MSPLIM_NS", out(reg) r, options(nomem, nostack, preserves_flags)) };
+    r
+}
+
+/// Writes `bits` to the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will be ignored.
+#[inline]
+pub unsafe fn write_ns(bits: u32) {
+    asm!("msr MSPLIM_NS, {}", in(reg) bits, options(nomem, nostack, preserves_flags));
+}
@@ -13,6 +13,17 @@ pub enum Primask {
 }
 
 impl Primask {
+    /// Decodes the value of the CPU register
+    #[cfg(cortex_m)]
+    #[inline]
+    fn from_bits(bits: u32) -> Self {
+        if bits & (1 << 0) == (1 << 0) {
+            Primask::Inactive
+        } else {
+            Primask::Active
+        }
+    }
+
     /// All exceptions with configurable priority are active
     #[inline]
     pub fn is_active(self) -> bool {
@@ -32,9 +43,29 @@ impl Primask {
 pub fn read() -> Primask {
     let r: u32;
     unsafe { asm!("mrs {}, PRIMASK", out(reg) r, options(nomem, nostack, preserves_flags)) };
-    if r & (1 << 0) == (1 << 0) {
-        Primask::Inactive
-    } else {
-        Primask::Active
-    }
+    Primask::from_bits(r)
+}
+
+/// Reads the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will return zeroes.
+#[cfg(armv8m)]
+#[inline]
+pub fn read_ns() -> Primask {
+    let r: u32;
+    unsafe { asm!("mrs {}, PRIMASK_NS", out(reg) r, options(nomem, nostack, preserves_flags)) };
+    Primask::from_bits(r)
+}
+
+/// Writes to the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will be ignored.
+#[cfg(armv8m)]
+#[inline]
+pub unsafe fn write_ns(primask: Primask) {
+    let bits: u32 = match primask {
+        Primask::Active => 0,
+        Primask::Inactive => 1,
+    };
+    asm!("msr PRIMASK_NS, {}", in(reg) bits, options(nomem, nostack, preserves_flags));
 }
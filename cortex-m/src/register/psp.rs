@@ -20,3 +20,23 @@ pub unsafe fn write(bits: u32) {
     // if MSP is currently being used as the stack pointer.
     asm!("msr PSP, {}", in(reg) bits, options(nomem, nostack, preserves_flags));
 }
+
+/// Reads the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will return zeroes.
+#[cfg(armv8m)]
+#[inline]
+pub fn read_ns() -> u32 {
+    let r;
+    unsafe { asm!("mrs {}, PSP_NS", out(reg) r, options(nomem, nostack, preserves_flags)) };
+    r
+}
+
+/// Writes `bits` to the Non-Secure CPU register from Secure state.
+///
+/// Executing this function in Non-Secure state will be ignored.
+#[cfg(armv8m)]
+#[inline]
+pub unsafe fn write_ns(bits: u32) {
+    asm!("msr PSP_NS, {}", in(reg) bits, options(nomem, nostack, preserves_flags));
+}
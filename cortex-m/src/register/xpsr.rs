@@ -0,0 +1,59 @@
+//! Combined Program Status Register
+//!
+//! The xPSR is the combination of the APSR, the IPSR and the EPSR. Its EPSR bits always read as
+//! zero with `MRS`, they are only meaningful in a stacked xPSR, see
+//! [`ExceptionFrame::xpsr`](crate::exception::ExceptionFrame::xpsr) and [`Xpsr::from_bits`].
+
+#[cfg(cortex_m)]
+use core::arch::asm;
+
+use super::apsr::Apsr;
+use super::epsr::Epsr;
+use super::ipsr::Ipsr;
+
+/// Combined Program Status Register
+#[derive(Clone, Copy, Debug)]
+pub struct Xpsr {
+    bits: u32,
+}
+
+impl Xpsr {
+    /// Creates an `Xpsr` from raw bits, for example from a stacked xPSR value
+    #[inline]
+    pub fn from_bits(bits: u32) -> Self {
+        Xpsr { bits }
+    }
+
+    /// Returns the contents of the register as raw bits
+    #[inline]
+    pub fn bits(self) -> u32 {
+        self.bits
+    }
+
+    /// Application Program Status Register view
+    #[inline]
+    pub fn apsr(self) -> Apsr {
+        Apsr::from_bits(self.bits)
+    }
+
+    /// Interrupt Program Status Register view
+    #[inline]
+    pub fn ipsr(self) -> Ipsr {
+        Ipsr::from_bits(self.bits)
+    }
+
+    /// Execution Program Status Register view
+    #[inline]
+    pub fn epsr(self) -> Epsr {
+        Epsr::from_bits(self.bits)
+    }
+}
+
+/// Reads the CPU register
+#[cfg(cortex_m)]
+#[inline]
+pub fn read() -> Xpsr {
+    let bits;
+    unsafe { asm!("mrs {}, XPSR", out(reg) bits, options(nomem, nostack, preserves_flags)) };
+    Xpsr { bits }
+}
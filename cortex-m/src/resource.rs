@@ -0,0 +1,282 @@
+//! Priority-ceiling resources
+//!
+//! A [`Resource`] protects data shared between tasks (Thread mode and interrupt handlers) of
+//! different priorities, following the Stack Resource Policy: the resource has a ceiling, the
+//! highest priority of the tasks that access it, and locking it raises the running priority to
+//! that ceiling. The tasks that do not share the resource and have a higher priority than the
+//! ceiling keep running during the lock.
+//!
+//! On ARMv7-M and ARMv8-M Mainline the running priority is raised through BASEPRI. ARMv6-M and
+//! ARMv8-M Baseline do not implement BASEPRI: the lock instead masks, in the NVIC, the enabled
+//! device interrupts whose priority is at or below the ceiling. PendSV and SysTick can not be
+//! masked that way; when one of them has a priority at or below the ceiling, the lock disables
+//! all interrupts through PRIMASK instead. SVCall is only taken on an `SVC` instruction and must
+//! not be requested from inside a lock.
+//!
+//! The ceiling is a raw priority byte, as written to the NVIC and SCB priority registers: lower
+//! values are higher priorities. Only the implemented priority bits are used, and at least one
+//! of them must be set in the ceiling.
+//!
+//! # Example
+//!
+//! ``` no_run
+//! use cortex_m::resource::Resource;
+//!
+//! // Shared with interrupt handlers running at priority 0x40 and below
+//! static COUNTER: Resource<u32, 0x40> = Resource::new(0);
+//!
+//! fn increment() {
+//!     COUNTER.lock(|counter| *counter += 1);
+//! }
+//! ```
+
+use core::cell::UnsafeCell;
+use core::sync::atomic::{compiler_fence, AtomicBool, Ordering};
+
+use crate::interrupt::InterruptNumber;
+use crate::peripheral::scb::VectActive;
+use crate::peripheral::{NVIC, SCB};
+
+/// Data protected by a priority ceiling `CEILING`
+///
+/// `CEILING` must be at least as high a priority as the one of every task accessing the
+/// resource, that is numerically lower or equal. It can not be zero: a BASEPRI of zero does not
+/// mask any interrupt, this is checked at compile time. It must also keep a non-zero value once
+/// the unimplemented priority bits are cleared, which is checked when the resource is locked.
+pub struct Resource<T, const CEILING: u8> {
+    data: UnsafeCell<T>,
+    locked: AtomicBool,
+}
+
+// NOTE the data can be accessed from different tasks, it must be `Send`
+unsafe impl<T, const CEILING: u8> Sync for Resource<T, CEILING> where T: Send {}
+
+impl<T, const CEILING: u8> Resource<T, CEILING> {
+    const CEILING_IS_VALID: () = assert!(CEILING != 0, "the ceiling of a resource can not be 0");
+
+    /// Creates a new resource
+    #[inline]
+    pub const fn new(data: T) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::CEILING_IS_VALID;
+
+        Resource {
+            data: UnsafeCell::new(data),
+            locked: AtomicBool::new(false),
+        }
+    }
+
+    /// Returns the ceiling of the resource
+    #[inline]
+    pub const fn ceiling(&self) -> u8 {
+        CEILING
+    }
+
+    /// Returns a mutable reference to the data
+    ///
+    /// No lock is needed since the resource is mutably borrowed.
+    #[inline]
+    pub fn get_mut(&mut self) -> &mut T {
+        self.data.get_mut()
+    }
+
+    /// Runs `f` with exclusive access to the data, the running priority raised to the ceiling
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ceiling has none of the implemented priority bits set, if the running
+    /// exception has a higher preemption priority than the ceiling, and if the resource is
+    /// already locked, which happens when it is locked again from `f`.
+    #[inline]
+    pub fn lock<R>(&self, f: impl FnOnce(&mut T) -> R) -> R {
+        let implemented = implemented_priority_mask();
+        let ceiling = CEILING & implemented;
+        assert!(
+            ceiling != 0,
+            "the ceiling of the resource has no implemented priority bit"
+        );
+
+        // Only the preemption (group) priority decides whether a task can preempt the lock, the
+        // subpriority is ignored as it is by BASEPRI.
+        let group = group_priority_mask(implemented);
+        assert!(
+            running_priority().map_or(true, |priority| priority & group >= ceiling & group),
+            "resource locked from a task with a higher priority than its ceiling"
+        );
+
+        let guard = Ceiling::raise(ceiling);
+
+        // Only tasks up to the ceiling can access the resource and they are all masked: the flag
+        // can not change between the load and the store.
+        assert!(
+            !self.locked.load(Ordering::Relaxed),
+            "resource is already locked"
+        );
+        self.locked.store(true, Ordering::Relaxed);
+        // Keep the accesses to the data made by `f` between the two stores of the flag.
+        compiler_fence(Ordering::SeqCst);
+
+        let r = f(unsafe { &mut *self.data.get() });
+
+        compiler_fence(Ordering::SeqCst);
+        self.locked.store(false, Ordering::Relaxed);
+        drop(guard);
+        r
+    }
+}
+
+/// Running priority saved by `Ceiling::raise`, restored on drop
+#[cfg(not(any(armv6m, armv8m_base)))]
+struct Ceiling {
+    previous: u8,
+}
+
+/// Interrupts masked by `Ceiling::raise`, unmasked on drop
+#[cfg(any(armv6m, armv8m_base))]
+enum Ceiling {
+    /// Device interrupts up to the ceiling, that were enabled, masked in the NVIC
+    Nvic(crate::peripheral::nvic::NvicMask),
+    /// All interrupts disabled through PRIMASK, that was `Active` before the lock
+    Primask(crate::register::primask::Primask),
+}
+
+impl Ceiling {
+    /// Raises the running priority to `ceiling`, which only has implemented bits set
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    #[inline]
+    fn raise(ceiling: u8) -> Self {
+        let previous = crate::register::basepri::read();
+        // BASEPRI_MAX only raises the running priority, nested locks of resources with a lower
+        // ceiling leave it unchanged
+        crate::register::basepri_max::write(ceiling);
+        // Ensure no subsequent memory accesses are reordered to before BASEPRI is raised.
+        compiler_fence(Ordering::SeqCst);
+        Ceiling { previous }
+    }
+
+    /// Raises the running priority to `ceiling`, which only has implemented bits set
+    #[cfg(any(armv6m, armv8m_base))]
+    #[inline]
+    fn raise(ceiling: u8) -> Self {
+        use crate::peripheral::scb::SystemHandler;
+
+        // PendSV and SysTick can not be masked in the NVIC
+        if SCB::get_priority(SystemHandler::PendSV) >= ceiling
+            || SCB::get_priority(SystemHandler::SysTick) >= ceiling
+        {
+            let primask = crate::register::primask::read();
+            crate::interrupt::disable();
+            return Ceiling::Primask(primask);
+        }
+
+        let mut mask = crate::peripheral::nvic::NvicMask::new();
+        for irqn in 0..NVIC::interrupt_lines() as u16 {
+            if NVIC::get_priority(Irq(irqn)) >= ceiling {
+                mask.insert(Irq(irqn));
+            }
+        }
+
+        let enabled = NVIC::mask_set(&mask);
+        compiler_fence(Ordering::SeqCst);
+        Ceiling::Nvic(enabled)
+    }
+}
+
+impl Drop for Ceiling {
+    #[inline]
+    fn drop(&mut self) {
+        // Ensure no preceeding memory accesses are reordered to after the priority is lowered.
+        compiler_fence(Ordering::SeqCst);
+
+        #[cfg(not(any(armv6m, armv8m_base)))]
+        unsafe {
+            crate::register::basepri::write(self.previous)
+        }
+
+        #[cfg(any(armv6m, armv8m_base))]
+        match self {
+            // NOTE(unsafe) only the interrupts that were enabled before the lock are enabled again
+            Ceiling::Nvic(enabled) => unsafe { NVIC::restore(enabled) },
+            Ceiling::Primask(primask) => {
+                if primask.is_active() {
+                    unsafe { crate::interrupt::enable() }
+                }
+            }
+        }
+    }
+}
+
+/// Raw device interrupt number
+#[derive(Clone, Copy)]
+struct Irq(u16);
+
+unsafe impl InterruptNumber for Irq {
+    #[inline]
+    fn number(self) -> u16 {
+        self.0
+    }
+}
+
+/// Returns the priority of the running exception, `None` in Thread mode
+///
+/// NMI and HardFault have a fixed priority higher than any configurable priority, `Some(0)` is
+/// returned for them.
+#[inline]
+fn running_priority() -> Option<u8> {
+    match SCB::vect_active() {
+        VectActive::ThreadMode => None,
+        VectActive::Exception(exception) => {
+            Some(exception.system_handler().map_or(0, SCB::get_priority))
+        }
+        VectActive::Interrupt { irqn } => Some(NVIC::get_priority(Irq(irqn))),
+    }
+}
+
+/// Returns the mask of the preemption priority bits among the `implemented` priority bits
+///
+/// ARMv6-M and ARMv8-M Baseline have no subpriority. Otherwise the preemption priority is made
+/// of bits `7` to `PRIGROUP + 1` of the priority byte.
+#[inline]
+fn group_priority_mask(implemented: u8) -> u8 {
+    #[cfg(any(armv6m, armv8m_base))]
+    {
+        implemented
+    }
+
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    {
+        let prigroup = u32::from(SCB::priority_grouping());
+        implemented & (0xFF_u32 << (prigroup + 1)) as u8
+    }
+}
+
+/// Returns the mask of the implemented priority bits
+///
+/// ARMv6-M and ARMv8-M Baseline implement two bits. Otherwise the mask is detected once, by
+/// writing `0xFF` to BASEPRI with interrupts disabled and reading it back.
+#[inline]
+fn implemented_priority_mask() -> u8 {
+    #[cfg(any(armv6m, armv8m_base))]
+    {
+        0xC0
+    }
+
+    #[cfg(not(any(armv6m, armv8m_base)))]
+    {
+        // zero until detected, a valid mask has at least one bit set
+        static MASK: core::sync::atomic::AtomicU8 = core::sync::atomic::AtomicU8::new(0);
+
+        let mut mask = MASK.load(Ordering::Relaxed);
+        if mask == 0 {
+            mask = crate::interrupt::free(|_| unsafe {
+                let previous = crate::register::basepri::read();
+                crate::register::basepri::write(0xFF);
+                let mask = crate::register::basepri::read();
+                crate::register::basepri::write(previous);
+                mask
+            });
+            MASK.store(mask, Ordering::Relaxed);
+        }
+        mask
+    }
+}